@@ -0,0 +1,26 @@
+ALTER TABLE matches RENAME TO matches_old;
+
+CREATE TABLE IF NOT EXISTS matches (
+    channel INTEGER PRIMARY KEY NOT NULL,
+    user1 INTEGER NOT NULL,
+    user2 INTEGER NOT NULL,
+    region INTEGER NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
+
+INSERT INTO matches(channel, user1, user2, region, timestamp) SELECT channel, user1, user2, COALESCE((SELECT uid / 100000000 FROM connections WHERE user = user1 LIMIT 1), 0) region, CURRENT_TIMESTAMP timestamp FROM matches_old;
+DROP TABLE matches_old;
+
+CREATE TABLE IF NOT EXISTS match_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    channel INTEGER NOT NULL,
+    user1 INTEGER NOT NULL,
+    user2 INTEGER NOT NULL,
+    region INTEGER NOT NULL,
+    started TIMESTAMP NOT NULL,
+    ended TIMESTAMP NOT NULL,
+    disbander INTEGER,
+    reason TEXT,
+    rating1 INTEGER,
+    rating2 INTEGER
+);
@@ -0,0 +1,3 @@
+ALTER TABLE matches ADD activity1 TIMESTAMP;
+ALTER TABLE matches ADD activity2 TIMESTAMP;
+ALTER TABLE matches ADD warned INTEGER;
@@ -0,0 +1,4 @@
+ALTER TABLE matches ADD applied1 TIMESTAMP;
+ALTER TABLE matches ADD applied2 TIMESTAMP;
+ALTER TABLE match_history ADD applied1 TIMESTAMP;
+ALTER TABLE match_history ADD applied2 TIMESTAMP;
@@ -0,0 +1,15 @@
+ALTER TABLE warns RENAME TO warns_old;
+
+CREATE TABLE IF NOT EXISTS warns (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user integer NOT NULL,
+    moderator integer NOT NULL,
+    reason text NOT NULL,
+    dm boolean NOT NULL,
+    message text,
+    timestamp TIMESTAMP NOT NULL
+);
+
+-- Older warns have no known creation time, the epoch keeps them out of escalation windows
+INSERT INTO warns(id, user, moderator, reason, dm, message, timestamp) SELECT id, user, moderator, reason, dm, message, '1970-01-01 00:00:00' timestamp FROM warns_old;
+DROP TABLE warns_old;
@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS escalations (
+    warns INTEGER PRIMARY KEY NOT NULL,
+    days INTEGER NOT NULL,
+    action TEXT NOT NULL,
+    duration INTEGER
+);
+
+INSERT INTO escalations(warns, days, action, duration) VALUES (3, 30, 'timeout', 60), (5, 30, 'timeout', 1440), (7, 30, 'ban', NULL);
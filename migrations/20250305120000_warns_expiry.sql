@@ -0,0 +1,7 @@
+ALTER TABLE warns ADD severity TEXT NOT NULL DEFAULT 'moderate';
+ALTER TABLE warns ADD expires TIMESTAMP;
+
+UPDATE warns SET expires = datetime(timestamp, '+90 days') WHERE timestamp > '1970-01-01 00:00:00';
+
+-- Older warns have no known creation time, so they expire 90 days after the migration instead
+UPDATE warns SET expires = datetime('now', '+90 days') WHERE timestamp <= '1970-01-01 00:00:00';
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS cases (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind text NOT NULL,
+    user integer NOT NULL,
+    moderator integer NOT NULL,
+    reason text NOT NULL,
+    duration integer,
+    dm boolean NOT NULL,
+    message text,
+    timestamp TIMESTAMP NOT NULL
+);
@@ -0,0 +1,18 @@
+-- Ban counts only recorded the moderator, so they are kept as totals that only count towards all time stats
+CREATE TABLE IF NOT EXISTS legacy_ban_counts (
+    moderator INTEGER PRIMARY KEY NOT NULL,
+    count INTEGER NOT NULL
+);
+
+INSERT INTO legacy_ban_counts(moderator, count) SELECT user, count FROM bans WHERE count > 0;
+
+DROP TABLE IF EXISTS bans;
+
+CREATE TABLE IF NOT EXISTS purges (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    moderator integer NOT NULL,
+    channel integer NOT NULL,
+    user integer,
+    count integer NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
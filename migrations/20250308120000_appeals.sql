@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS appeals (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind text NOT NULL,
+    reference integer NOT NULL,
+    user integer NOT NULL,
+    content text NOT NULL,
+    status text NOT NULL,
+    moderator integer,
+    response text,
+    channel integer,
+    message integer,
+    timestamp TIMESTAMP NOT NULL
+);
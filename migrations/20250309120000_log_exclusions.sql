@@ -0,0 +1,3 @@
+CREATE TABLE IF NOT EXISTS log_exclusions (
+    channel INTEGER PRIMARY KEY NOT NULL
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS cached_messages (
+    channel integer NOT NULL,
+    message integer NOT NULL,
+    data text NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (channel, message)
+);
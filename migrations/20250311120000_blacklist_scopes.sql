@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS blacklist_exemptions (
+    emoji TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    PRIMARY KEY (emoji, kind, target)
+);
+
+CREATE TABLE IF NOT EXISTS blacklist_offenses (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    user integer NOT NULL,
+    emoji text NOT NULL,
+    channel integer NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE blacklist ADD COLUMN action TEXT NOT NULL DEFAULT 'delete';
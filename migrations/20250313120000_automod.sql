@@ -0,0 +1,14 @@
+CREATE TABLE IF NOT EXISTS automod_rules (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    kind text NOT NULL,
+    value text NOT NULL,
+    action text NOT NULL,
+    duration integer
+);
+
+CREATE TABLE IF NOT EXISTS automod_exemptions (
+    rule INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    PRIMARY KEY (rule, kind, target)
+);
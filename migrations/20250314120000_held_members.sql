@@ -0,0 +1,5 @@
+CREATE TABLE IF NOT EXISTS held_members (
+    user INTEGER PRIMARY KEY NOT NULL,
+    reason TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL
+);
@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS backfills (
+    name TEXT PRIMARY KEY NOT NULL,
+    cursor INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS backfill_skips (
+    name TEXT NOT NULL,
+    user INTEGER NOT NULL,
+    reason TEXT NOT NULL,
+    PRIMARY KEY (name, user)
+);
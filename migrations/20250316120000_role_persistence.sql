@@ -0,0 +1,13 @@
+ALTER TABLE user_roles ADD COLUMN timestamp TIMESTAMP NOT NULL DEFAULT '1970-01-01 00:00:00';
+
+UPDATE user_roles SET timestamp = datetime('now');
+
+CREATE TABLE IF NOT EXISTS member_roles (
+    user INTEGER NOT NULL,
+    role INTEGER NOT NULL,
+    PRIMARY KEY (user, role)
+);
+
+CREATE TABLE IF NOT EXISTS persist_exclusions (
+    role INTEGER PRIMARY KEY NOT NULL
+);
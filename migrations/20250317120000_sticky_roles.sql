@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS sticky_roles (
+    role INTEGER PRIMARY KEY NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS sticky_assignments (
+    user INTEGER NOT NULL,
+    role INTEGER NOT NULL,
+    moderator INTEGER,
+    timestamp TIMESTAMP NOT NULL,
+    PRIMARY KEY (user, role)
+);
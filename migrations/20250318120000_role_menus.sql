@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS role_menus (
+    id integer PRIMARY KEY AUTOINCREMENT NOT NULL,
+    channel integer NOT NULL,
+    message integer NOT NULL,
+    title text NOT NULL,
+    description text NOT NULL,
+    kind text NOT NULL,
+    exclusive boolean NOT NULL,
+    required integer,
+    max integer
+);
+
+CREATE TABLE IF NOT EXISTS role_menu_roles (
+    menu INTEGER NOT NULL,
+    role INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    PRIMARY KEY (menu, role)
+);
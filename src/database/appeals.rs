@@ -0,0 +1,117 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbAppeal {
+    pub id: i64,
+    pub kind: String,
+    pub reference: i64,
+    pub user: i64,
+    pub content: String,
+    pub status: String,
+    pub moderator: Option<i64>,
+    pub response: Option<String>,
+    pub channel: Option<i64>,
+    pub message: Option<i64>,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn set_appeal(appeal: &DbAppeal, pool: &SqlitePool) -> Result<i64> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO appeals(kind, reference, user, content, status, moderator, response, channel, message, timestamp) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        appeal.kind,
+        appeal.reference,
+        appeal.user,
+        appeal.content,
+        appeal.status,
+        appeal.moderator,
+        appeal.response,
+        appeal.channel,
+        appeal.message,
+        appeal.timestamp,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query!("SELECT LAST_INSERT_ROWID() as id")
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+    transaction.commit().await?;
+
+    Ok(id as i64)
+}
+
+pub async fn get_appeal_by_id(id: i64, pool: &SqlitePool) -> Result<DbAppeal> {
+    Ok(
+        sqlx::query_as!(DbAppeal, "SELECT * FROM appeals WHERE id = ?", id)
+            .fetch_one(pool)
+            .await?,
+    )
+}
+
+pub async fn get_appeal_by_kind_and_reference(
+    kind: &str,
+    reference: i64,
+    pool: &SqlitePool,
+) -> Result<Option<DbAppeal>> {
+    Ok(sqlx::query_as!(
+        DbAppeal,
+        "SELECT * FROM appeals WHERE kind = ? AND reference = ?",
+        kind,
+        reference
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn get_appeals_by_status(status: &str, pool: &SqlitePool) -> Result<Vec<DbAppeal>> {
+    Ok(sqlx::query_as!(
+        DbAppeal,
+        "SELECT * FROM appeals WHERE status = ? ORDER BY id",
+        status
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn update_appeal_message_by_id(
+    id: i64,
+    channel: i64,
+    message: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE appeals SET channel = ?, message = ? WHERE id = ?",
+        channel,
+        message,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn update_appeal_status_by_id(
+    id: i64,
+    status: &str,
+    moderator: i64,
+    response: Option<&str>,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE appeals SET status = ?, moderator = ?, response = ? WHERE id = ?",
+        status,
+        moderator,
+        response,
+        id
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
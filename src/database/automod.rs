@@ -0,0 +1,109 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbAutomodRule {
+    pub id: i64,
+    pub kind: String,
+    pub value: String,
+    pub action: String,
+    pub duration: Option<i64>,
+}
+
+pub async fn get_automod_rules(pool: &SqlitePool) -> Result<Vec<DbAutomodRule>> {
+    Ok(
+        sqlx::query_as!(DbAutomodRule, "SELECT * FROM automod_rules")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn get_automod_rule_by_id(id: i64, pool: &SqlitePool) -> Result<Option<DbAutomodRule>> {
+    Ok(sqlx::query_as!(
+        DbAutomodRule,
+        "SELECT * FROM automod_rules WHERE id = ?",
+        id
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn set_automod_rule(rule: &DbAutomodRule, pool: &SqlitePool) -> Result<i64> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO automod_rules(kind, value, action, duration) VALUES(?, ?, ?, ?)",
+        rule.kind,
+        rule.value,
+        rule.action,
+        rule.duration,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query!("SELECT LAST_INSERT_ROWID() as id")
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+    transaction.commit().await?;
+
+    Ok(id as i64)
+}
+
+pub async fn delete_automod_rule_by_id(id: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM automod_rules WHERE id = ?", id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query!("DELETE FROM automod_exemptions WHERE rule = ?", id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub struct DbAutomodExemption {
+    pub rule: i64,
+    pub kind: String,
+    pub target: i64,
+}
+
+pub async fn get_automod_exemptions(pool: &SqlitePool) -> Result<Vec<DbAutomodExemption>> {
+    Ok(
+        sqlx::query_as!(DbAutomodExemption, "SELECT * FROM automod_exemptions")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn set_automod_exemption(
+    exemption: &DbAutomodExemption,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO automod_exemptions(rule, kind, target) VALUES(?, ?, ?)",
+        exemption.rule,
+        exemption.kind,
+        exemption.target,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_automod_exemption(
+    exemption: &DbAutomodExemption,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM automod_exemptions WHERE rule = ? AND kind = ? AND target = ?",
+        exemption.rule,
+        exemption.kind,
+        exemption.target,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
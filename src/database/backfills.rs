@@ -0,0 +1,75 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbBackfill {
+    pub name: String,
+    pub cursor: i64,
+}
+
+pub async fn get_backfill_by_name(name: &str, pool: &SqlitePool) -> Result<Option<DbBackfill>> {
+    Ok(
+        sqlx::query_as!(DbBackfill, "SELECT * FROM backfills WHERE name = ?", name)
+            .fetch_optional(pool)
+            .await?,
+    )
+}
+
+pub async fn set_backfill(backfill: &DbBackfill, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO backfills(name, cursor) VALUES(?, ?)",
+        backfill.name,
+        backfill.cursor,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_backfill_by_name(name: &str, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM backfills WHERE name = ?", name)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub struct DbBackfillSkip {
+    pub name: String,
+    pub user: i64,
+    pub reason: String,
+}
+
+pub async fn get_backfill_skips_by_name(
+    name: &str,
+    pool: &SqlitePool,
+) -> Result<Vec<DbBackfillSkip>> {
+    Ok(sqlx::query_as!(
+        DbBackfillSkip,
+        "SELECT * FROM backfill_skips WHERE name = ?",
+        name
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_backfill_skip(skip: &DbBackfillSkip, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO backfill_skips(name, user, reason) VALUES(?, ?, ?)",
+        skip.name,
+        skip.user,
+        skip.reason,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_backfill_skips_by_name(name: &str, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM backfill_skips WHERE name = ?", name)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::SqlitePool;
 
 pub struct DbBlacklist {
     pub emoji: String,
+    pub action: String,
 }
 
 pub async fn get_blacklist(pool: &SqlitePool) -> Result<Vec<DbBlacklist>> {
@@ -13,8 +15,9 @@ pub async fn get_blacklist(pool: &SqlitePool) -> Result<Vec<DbBlacklist>> {
 
 pub async fn set_emoji(blacklist: DbBlacklist, pool: &SqlitePool) -> Result<()> {
     sqlx::query!(
-        "INSERT OR REPLACE INTO blacklist(emoji) VALUES(?)",
-        blacklist.emoji
+        "INSERT OR REPLACE INTO blacklist(emoji, action) VALUES(?, ?)",
+        blacklist.emoji,
+        blacklist.action
     )
     .execute(pool)
     .await?;
@@ -29,3 +32,115 @@ pub async fn delete_emoji_by_emoji(emoji: &str, pool: &SqlitePool) -> Result<()>
 
     Ok(())
 }
+
+pub struct DbBlacklistExemption {
+    pub emoji: String,
+    pub kind: String,
+    pub target: i64,
+}
+
+pub async fn get_blacklist_exemptions(pool: &SqlitePool) -> Result<Vec<DbBlacklistExemption>> {
+    Ok(
+        sqlx::query_as!(DbBlacklistExemption, "SELECT * FROM blacklist_exemptions")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn get_blacklist_exemptions_by_emoji(
+    emoji: &str,
+    pool: &SqlitePool,
+) -> Result<Vec<DbBlacklistExemption>> {
+    Ok(sqlx::query_as!(
+        DbBlacklistExemption,
+        "SELECT * FROM blacklist_exemptions WHERE emoji = ?",
+        emoji
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_blacklist_exemption(
+    exemption: &DbBlacklistExemption,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO blacklist_exemptions(emoji, kind, target) VALUES(?, ?, ?)",
+        exemption.emoji,
+        exemption.kind,
+        exemption.target,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_blacklist_exemption(
+    exemption: &DbBlacklistExemption,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM blacklist_exemptions WHERE emoji = ? AND kind = ? AND target = ?",
+        exemption.emoji,
+        exemption.kind,
+        exemption.target,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_blacklist_exemptions_by_emoji(emoji: &str, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM blacklist_exemptions WHERE emoji = ?", emoji)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub struct DbBlacklistOffense {
+    pub user: i64,
+    pub emoji: String,
+    pub channel: i64,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn get_blacklist_offenses(pool: &SqlitePool) -> Result<Vec<DbBlacklistOffense>> {
+    Ok(sqlx::query_as!(
+        DbBlacklistOffense,
+        "SELECT user, emoji, channel, timestamp FROM blacklist_offenses"
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_blacklist_offense_count_by_user_since(
+    user: i64,
+    since: NaiveDateTime,
+    pool: &SqlitePool,
+) -> Result<i64> {
+    Ok(sqlx::query!(
+        r#"SELECT COUNT(*) as "count: i64" FROM blacklist_offenses WHERE user = ? AND timestamp >= ?"#,
+        user,
+        since
+    )
+    .fetch_one(pool)
+    .await?
+    .count)
+}
+
+pub async fn set_blacklist_offense(offense: &DbBlacklistOffense, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO blacklist_offenses(user, emoji, channel, timestamp) VALUES(?, ?, ?, ?)",
+        offense.user,
+        offense.emoji,
+        offense.channel,
+        offense.timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
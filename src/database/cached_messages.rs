@@ -0,0 +1,61 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbCachedMessage {
+    pub channel: i64,
+    pub message: i64,
+    pub data: String,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn get_cached_message_by_channel_and_message(
+    channel: i64,
+    message: i64,
+    pool: &SqlitePool,
+) -> Result<Option<DbCachedMessage>> {
+    Ok(sqlx::query_as!(
+        DbCachedMessage,
+        "SELECT * FROM cached_messages WHERE channel = ? AND message = ?",
+        channel,
+        message
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn set_cached_message(cached_message: &DbCachedMessage, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO cached_messages(channel, message, data, timestamp) VALUES(?, ?, ?, ?)",
+        cached_message.channel,
+        cached_message.message,
+        cached_message.data,
+        cached_message.timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_cached_messages_over_limit(limit: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM cached_messages WHERE message NOT IN (SELECT message FROM (SELECT message, ROW_NUMBER() OVER (PARTITION BY channel ORDER BY message DESC) AS position FROM cached_messages) WHERE position <= ?)",
+        limit
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_cached_messages_before(
+    timestamp: NaiveDateTime,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!("DELETE FROM cached_messages WHERE timestamp < ?", timestamp)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
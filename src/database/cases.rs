@@ -0,0 +1,61 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbCase {
+    pub kind: String,
+    pub user: i64,
+    pub moderator: i64,
+    pub reason: String,
+    pub duration: Option<i64>,
+    pub dm: bool,
+    pub message: Option<String>,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn set_case(case: &DbCase, pool: &SqlitePool) -> Result<i64> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO cases(kind, user, moderator, reason, duration, dm, message, timestamp) VALUES(?, ?, ?, ?, ?, ?, ?, ?)",
+        case.kind,
+        case.user,
+        case.moderator,
+        case.reason,
+        case.duration,
+        case.dm,
+        case.message,
+        case.timestamp,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query!("SELECT LAST_INSERT_ROWID() as id")
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+    transaction.commit().await?;
+
+    Ok(id as i64)
+}
+
+pub async fn get_cases_since(since: NaiveDateTime, pool: &SqlitePool) -> Result<Vec<DbCase>> {
+    Ok(sqlx::query_as!(
+        DbCase,
+        "SELECT kind, user, moderator, reason, duration, dm, message, timestamp FROM cases WHERE timestamp >= ?",
+        since
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_case_by_id(id: i64, pool: &SqlitePool) -> Result<DbCase> {
+    Ok(sqlx::query_as!(
+        DbCase,
+        "SELECT kind, user, moderator, reason, duration, dm, message, timestamp FROM cases WHERE id = ?",
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
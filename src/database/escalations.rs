@@ -0,0 +1,40 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbEscalation {
+    pub warns: i64,
+    pub days: i64,
+    pub action: String,
+    pub duration: Option<i64>,
+}
+
+pub async fn get_escalations_order_by_warns_desc(pool: &SqlitePool) -> Result<Vec<DbEscalation>> {
+    Ok(sqlx::query_as!(
+        DbEscalation,
+        "SELECT * FROM escalations ORDER BY warns DESC"
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_escalation(escalation: &DbEscalation, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO escalations(warns, days, action, duration) VALUES(?, ?, ?, ?)",
+        escalation.warns,
+        escalation.days,
+        escalation.action,
+        escalation.duration,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_escalation_by_warns(warns: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM escalations WHERE warns = ?", warns)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
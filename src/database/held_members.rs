@@ -0,0 +1,46 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbHeldMember {
+    pub user: i64,
+    pub reason: String,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn get_held_members(pool: &SqlitePool) -> Result<Vec<DbHeldMember>> {
+    Ok(sqlx::query_as!(DbHeldMember, "SELECT * FROM held_members")
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn get_held_member_by_user(user: i64, pool: &SqlitePool) -> Result<Option<DbHeldMember>> {
+    Ok(sqlx::query_as!(
+        DbHeldMember,
+        "SELECT * FROM held_members WHERE user = ?",
+        user
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn set_held_member(held_member: &DbHeldMember, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO held_members(user, reason, timestamp) VALUES(?, ?, ?)",
+        held_member.user,
+        held_member.reason,
+        held_member.timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_held_member_by_user(user: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM held_members WHERE user = ?", user)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbLegacyBanCount {
+    pub moderator: i64,
+    pub count: i64,
+}
+
+pub async fn get_legacy_ban_counts(pool: &SqlitePool) -> Result<Vec<DbLegacyBanCount>> {
+    Ok(
+        sqlx::query_as!(DbLegacyBanCount, "SELECT * FROM legacy_ban_counts")
+            .fetch_all(pool)
+            .await?,
+    )
+}
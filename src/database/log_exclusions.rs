@@ -0,0 +1,46 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbLogExclusion {
+    pub channel: i64,
+}
+
+pub async fn get_log_exclusions(pool: &SqlitePool) -> Result<Vec<DbLogExclusion>> {
+    Ok(
+        sqlx::query_as!(DbLogExclusion, "SELECT * FROM log_exclusions")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn get_log_exclusion_by_channel(
+    channel: i64,
+    pool: &SqlitePool,
+) -> Result<Option<DbLogExclusion>> {
+    Ok(sqlx::query_as!(
+        DbLogExclusion,
+        "SELECT * FROM log_exclusions WHERE channel = ?",
+        channel
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn set_log_exclusion(exclusion: &DbLogExclusion, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO log_exclusions(channel) VALUES(?)",
+        exclusion.channel,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_log_exclusion_by_channel(channel: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM log_exclusions WHERE channel = ?", channel)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbMatchHistory {
+    pub channel: i64,
+    pub user1: i64,
+    pub user2: i64,
+    pub region: i64,
+    pub started: NaiveDateTime,
+    pub ended: NaiveDateTime,
+    pub disbander: Option<i64>,
+    pub reason: Option<String>,
+    pub rating1: Option<i64>,
+    pub rating2: Option<i64>,
+    pub applied1: Option<NaiveDateTime>,
+    pub applied2: Option<NaiveDateTime>,
+}
+
+pub async fn set_match_history(data: &DbMatchHistory, pool: &SqlitePool) -> Result<i64> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO match_history(channel, user1, user2, region, started, ended, disbander, reason, rating1, rating2, applied1, applied2) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        data.channel,
+        data.user1,
+        data.user2,
+        data.region,
+        data.started,
+        data.ended,
+        data.disbander,
+        data.reason,
+        data.rating1,
+        data.rating2,
+        data.applied1,
+        data.applied2,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query!("SELECT LAST_INSERT_ROWID() as id")
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+    transaction.commit().await?;
+
+    Ok(id as i64)
+}
+
+pub async fn get_match_histories(pool: &SqlitePool) -> Result<Vec<DbMatchHistory>> {
+    Ok(sqlx::query_as!(
+        DbMatchHistory,
+        "SELECT channel, user1, user2, region, started, ended, disbander, reason, rating1, rating2, applied1, applied2 FROM match_history"
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_match_history_by_id(id: i64, pool: &SqlitePool) -> Result<DbMatchHistory> {
+    Ok(sqlx::query_as!(
+        DbMatchHistory,
+        "SELECT channel, user1, user2, region, started, ended, disbander, reason, rating1, rating2, applied1, applied2 FROM match_history WHERE id = ?",
+        id
+    )
+    .fetch_one(pool)
+    .await?)
+}
+
+pub async fn get_match_histories_by_user(
+    user: i64,
+    pool: &SqlitePool,
+) -> Result<Vec<DbMatchHistory>> {
+    Ok(sqlx::query_as!(
+        DbMatchHistory,
+        "SELECT channel, user1, user2, region, started, ended, disbander, reason, rating1, rating2, applied1, applied2 FROM match_history WHERE user1 = ?1 OR user2 = ?1 ORDER BY ended DESC",
+        user
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_match_histories_by_users(
+    user1: i64,
+    user2: i64,
+    pool: &SqlitePool,
+) -> Result<Vec<DbMatchHistory>> {
+    Ok(sqlx::query_as!(
+        DbMatchHistory,
+        "SELECT channel, user1, user2, region, started, ended, disbander, reason, rating1, rating2, applied1, applied2 FROM match_history WHERE (user1 = ?1 AND user2 = ?2) OR (user1 = ?2 AND user2 = ?1)",
+        user1,
+        user2
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_match_history_rating(
+    id: i64,
+    user: i64,
+    rating: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE match_history SET rating1 = CASE WHEN user1 = ?2 THEN ?3 ELSE rating1 END, rating2 = CASE WHEN user2 = ?2 THEN ?3 ELSE rating2 END WHERE id = ?1",
+        id,
+        user,
+        rating
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
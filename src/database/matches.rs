@@ -1,18 +1,33 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::SqlitePool;
 
 pub struct DbMatch {
     pub channel: i64,
     pub user1: i64,
     pub user2: i64,
+    pub region: i64,
+    pub timestamp: NaiveDateTime,
+    pub activity1: Option<NaiveDateTime>,
+    pub activity2: Option<NaiveDateTime>,
+    pub warned: Option<i64>,
+    pub applied1: Option<NaiveDateTime>,
+    pub applied2: Option<NaiveDateTime>,
 }
 
 pub async fn set_match(data: &DbMatch, pool: &SqlitePool) -> Result<()> {
     sqlx::query!(
-        "INSERT OR REPLACE INTO matches(channel, user1, user2) VALUES(?, ?, ?)",
+        "INSERT OR REPLACE INTO matches(channel, user1, user2, region, timestamp, activity1, activity2, warned, applied1, applied2) VALUES(?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
         data.channel,
         data.user1,
         data.user2,
+        data.region,
+        data.timestamp,
+        data.activity1,
+        data.activity2,
+        data.warned,
+        data.applied1,
+        data.applied2,
     )
     .execute(pool)
     .await?;
@@ -20,6 +35,12 @@ pub async fn set_match(data: &DbMatch, pool: &SqlitePool) -> Result<()> {
     Ok(())
 }
 
+pub async fn get_matches(pool: &SqlitePool) -> Result<Vec<DbMatch>> {
+    Ok(sqlx::query_as!(DbMatch, "SELECT * FROM matches")
+        .fetch_all(pool)
+        .await?)
+}
+
 pub async fn get_match_by_channel(channel: i64, pool: &SqlitePool) -> Result<DbMatch> {
     Ok(
         sqlx::query_as!(DbMatch, "SELECT * FROM matches WHERE channel = ?", channel)
@@ -46,3 +67,33 @@ pub async fn delete_match_by_channel(channel: i64, pool: &SqlitePool) -> Result<
 
     Ok(())
 }
+
+pub async fn set_match_activity(
+    channel: i64,
+    user: i64,
+    timestamp: NaiveDateTime,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "UPDATE matches SET activity1 = CASE WHEN user1 = ?2 THEN ?3 ELSE activity1 END, activity2 = CASE WHEN user2 = ?2 THEN ?3 ELSE activity2 END, warned = CASE WHEN warned = ?2 THEN NULL ELSE warned END WHERE channel = ?1",
+        channel,
+        user,
+        timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_match_warned(channel: i64, warned: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "UPDATE matches SET warned = ? WHERE channel = ?",
+        warned,
+        channel
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
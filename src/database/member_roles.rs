@@ -0,0 +1,65 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbMemberRole {
+    pub user: i64,
+    pub role: i64,
+}
+
+pub async fn get_member_roles(pool: &SqlitePool) -> Result<Vec<DbMemberRole>> {
+    Ok(sqlx::query_as!(DbMemberRole, "SELECT * FROM member_roles")
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn get_member_roles_by_user(user: i64, pool: &SqlitePool) -> Result<Vec<DbMemberRole>> {
+    Ok(sqlx::query_as!(
+        DbMemberRole,
+        "SELECT * FROM member_roles WHERE user = ?",
+        user
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_member_role(data: &DbMemberRole, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO member_roles(user, role) VALUES(?, ?)",
+        data.user,
+        data.role,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn set_member_roles(data: &[DbMemberRole], pool: &SqlitePool) -> Result<()> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!("DELETE FROM member_roles")
+        .execute(&mut *transaction)
+        .await?;
+
+    for member_role in data {
+        sqlx::query!(
+            "INSERT OR REPLACE INTO member_roles(user, role) VALUES(?, ?)",
+            member_role.user,
+            member_role.role,
+        )
+        .execute(&mut *transaction)
+        .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
+
+pub async fn delete_member_roles_by_user(user: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM member_roles WHERE user = ?", user)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
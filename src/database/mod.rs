@@ -1,21 +1,51 @@
-mod bans;
+mod appeals;
+mod automod;
+mod backfills;
 mod blacklist;
+mod cached_messages;
 mod candidates;
+mod cases;
 mod connections;
+mod escalations;
+mod held_members;
+mod legacy_ban_counts;
+mod log_exclusions;
+mod match_history;
 mod matches;
+mod member_roles;
+mod persist_exclusions;
 mod posts;
+mod purges;
+mod role_menus;
 mod roles;
+mod settings;
+mod sticky_roles;
 mod user_roles;
 mod verifications;
 mod warns;
 
-pub use bans::*;
+pub use appeals::*;
+pub use automod::*;
+pub use backfills::*;
 pub use blacklist::*;
+pub use cached_messages::*;
 pub use candidates::*;
+pub use cases::*;
 pub use connections::*;
+pub use escalations::*;
+pub use held_members::*;
+pub use legacy_ban_counts::*;
+pub use log_exclusions::*;
+pub use match_history::*;
 pub use matches::*;
+pub use member_roles::*;
+pub use persist_exclusions::*;
 pub use posts::*;
+pub use purges::*;
+pub use role_menus::*;
 pub use roles::*;
+pub use settings::*;
+pub use sticky_roles::*;
 pub use user_roles::*;
 pub use verifications::*;
 pub use warns::*;
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbPersistExclusion {
+    pub role: i64,
+}
+
+pub async fn get_persist_exclusions(pool: &SqlitePool) -> Result<Vec<DbPersistExclusion>> {
+    Ok(
+        sqlx::query_as!(DbPersistExclusion, "SELECT * FROM persist_exclusions")
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn set_persist_exclusion(
+    exclusion: &DbPersistExclusion,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO persist_exclusions(role) VALUES(?)",
+        exclusion.role,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_persist_exclusion_by_role(role: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM persist_exclusions WHERE role = ?", role)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
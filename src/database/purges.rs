@@ -0,0 +1,36 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbPurge {
+    pub moderator: i64,
+    pub channel: i64,
+    pub user: Option<i64>,
+    pub count: i64,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn set_purge(purge: &DbPurge, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT INTO purges(moderator, channel, user, count, timestamp) VALUES(?, ?, ?, ?, ?)",
+        purge.moderator,
+        purge.channel,
+        purge.user,
+        purge.count,
+        purge.timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn get_purges_since(since: NaiveDateTime, pool: &SqlitePool) -> Result<Vec<DbPurge>> {
+    Ok(sqlx::query_as!(
+        DbPurge,
+        "SELECT moderator, channel, user, count, timestamp FROM purges WHERE timestamp >= ?",
+        since
+    )
+    .fetch_all(pool)
+    .await?)
+}
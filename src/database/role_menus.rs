@@ -0,0 +1,115 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbRoleMenu {
+    pub id: i64,
+    pub channel: i64,
+    pub message: i64,
+    pub title: String,
+    pub description: String,
+    pub kind: String,
+    pub exclusive: bool,
+    pub required: Option<i64>,
+    pub max: Option<i64>,
+}
+
+pub async fn get_role_menus(pool: &SqlitePool) -> Result<Vec<DbRoleMenu>> {
+    Ok(sqlx::query_as!(DbRoleMenu, "SELECT * FROM role_menus")
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn get_role_menu_by_id(id: i64, pool: &SqlitePool) -> Result<Option<DbRoleMenu>> {
+    Ok(
+        sqlx::query_as!(DbRoleMenu, "SELECT * FROM role_menus WHERE id = ?", id)
+            .fetch_optional(pool)
+            .await?,
+    )
+}
+
+pub async fn set_role_menu(menu: &DbRoleMenu, pool: &SqlitePool) -> Result<i64> {
+    let mut transaction = pool.begin().await?;
+
+    sqlx::query!(
+        "INSERT INTO role_menus(channel, message, title, description, kind, exclusive, required, max) VALUES(?, ?, ?, ?, ?, ?, ?, ?)",
+        menu.channel,
+        menu.message,
+        menu.title,
+        menu.description,
+        menu.kind,
+        menu.exclusive,
+        menu.required,
+        menu.max,
+    )
+    .execute(&mut *transaction)
+    .await?;
+
+    let id = sqlx::query!("SELECT LAST_INSERT_ROWID() as id")
+        .fetch_one(&mut *transaction)
+        .await?
+        .id;
+
+    transaction.commit().await?;
+
+    Ok(id as i64)
+}
+
+pub async fn delete_role_menu_by_id(id: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM role_menus WHERE id = ?", id)
+        .execute(pool)
+        .await?;
+
+    sqlx::query!("DELETE FROM role_menu_roles WHERE menu = ?", id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub struct DbRoleMenuRole {
+    pub menu: i64,
+    pub role: i64,
+    pub label: String,
+}
+
+pub async fn get_role_menu_roles_by_menu(
+    menu: i64,
+    pool: &SqlitePool,
+) -> Result<Vec<DbRoleMenuRole>> {
+    Ok(sqlx::query_as!(
+        DbRoleMenuRole,
+        "SELECT * FROM role_menu_roles WHERE menu = ? ORDER BY rowid",
+        menu
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn set_role_menu_role(menu_role: &DbRoleMenuRole, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO role_menu_roles(menu, role, label) VALUES(?, ?, ?)",
+        menu_role.menu,
+        menu_role.role,
+        menu_role.label,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_role_menu_role_by_menu_and_role(
+    menu: i64,
+    role: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM role_menu_roles WHERE menu = ? AND role = ?",
+        menu,
+        role
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
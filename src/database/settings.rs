@@ -0,0 +1,41 @@
+use anyhow::Result;
+use sqlx::SqlitePool;
+
+pub struct DbSetting {
+    pub key: String,
+    pub value: String,
+}
+
+pub async fn get_settings(pool: &SqlitePool) -> Result<Vec<DbSetting>> {
+    Ok(sqlx::query_as!(DbSetting, "SELECT * FROM settings")
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn get_setting_by_key(key: &str, pool: &SqlitePool) -> Result<Option<DbSetting>> {
+    Ok(
+        sqlx::query_as!(DbSetting, "SELECT * FROM settings WHERE key = ?", key)
+            .fetch_optional(pool)
+            .await?,
+    )
+}
+
+pub async fn set_setting(setting: &DbSetting, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO settings(key, value) VALUES(?, ?)",
+        setting.key,
+        setting.value,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_setting_by_key(key: &str, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM settings WHERE key = ?", key)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+
+pub struct DbStickyRole {
+    pub role: i64,
+}
+
+pub async fn get_sticky_roles(pool: &SqlitePool) -> Result<Vec<DbStickyRole>> {
+    Ok(sqlx::query_as!(DbStickyRole, "SELECT * FROM sticky_roles")
+        .fetch_all(pool)
+        .await?)
+}
+
+pub async fn set_sticky_role(sticky_role: &DbStickyRole, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO sticky_roles(role) VALUES(?)",
+        sticky_role.role,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_sticky_role_by_role(role: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM sticky_roles WHERE role = ?", role)
+        .execute(pool)
+        .await?;
+
+    sqlx::query!("DELETE FROM sticky_assignments WHERE role = ?", role)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub struct DbStickyAssignment {
+    pub user: i64,
+    pub role: i64,
+    pub moderator: Option<i64>,
+    pub timestamp: NaiveDateTime,
+}
+
+pub async fn get_sticky_assignments_by_user(
+    user: i64,
+    pool: &SqlitePool,
+) -> Result<Vec<DbStickyAssignment>> {
+    Ok(sqlx::query_as!(
+        DbStickyAssignment,
+        "SELECT * FROM sticky_assignments WHERE user = ?",
+        user
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_sticky_assignment_by_user_and_role(
+    user: i64,
+    role: i64,
+    pool: &SqlitePool,
+) -> Result<Option<DbStickyAssignment>> {
+    Ok(sqlx::query_as!(
+        DbStickyAssignment,
+        "SELECT * FROM sticky_assignments WHERE user = ? AND role = ?",
+        user,
+        role
+    )
+    .fetch_optional(pool)
+    .await?)
+}
+
+pub async fn set_sticky_assignment(
+    assignment: &DbStickyAssignment,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "INSERT OR REPLACE INTO sticky_assignments(user, role, moderator, timestamp) VALUES(?, ?, ?, ?)",
+        assignment.user,
+        assignment.role,
+        assignment.moderator,
+        assignment.timestamp,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_sticky_assignment_by_user_and_role(
+    user: i64,
+    role: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM sticky_assignments WHERE user = ? AND role = ?",
+        user,
+        role
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
@@ -1,16 +1,19 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::SqlitePool;
 
 pub struct DbUserRole {
     pub user: i64,
     pub role: i64,
+    pub timestamp: NaiveDateTime,
 }
 
 pub async fn set_user_role(data: &DbUserRole, pool: &SqlitePool) -> Result<()> {
     sqlx::query!(
-        "INSERT OR REPLACE INTO user_roles(user, role) VALUES(?, ?)",
+        "INSERT OR REPLACE INTO user_roles(user, role, timestamp) VALUES(?, ?, ?)",
         data.user,
         data.role,
+        data.timestamp,
     )
     .execute(pool)
     .await?;
@@ -18,6 +21,12 @@ pub async fn set_user_role(data: &DbUserRole, pool: &SqlitePool) -> Result<()> {
     Ok(())
 }
 
+pub async fn get_user_roles(pool: &SqlitePool) -> Result<Vec<DbUserRole>> {
+    Ok(sqlx::query_as!(DbUserRole, "SELECT * FROM user_roles")
+        .fetch_all(pool)
+        .await?)
+}
+
 pub async fn get_user_roles_by_user(user: i64, pool: &SqlitePool) -> Result<Vec<DbUserRole>> {
     Ok(
         sqlx::query_as!(DbUserRole, "SELECT * FROM user_roles WHERE user == ?", user)
@@ -33,3 +42,27 @@ pub async fn delete_user_roles_by_user(user: i64, pool: &SqlitePool) -> Result<(
 
     Ok(())
 }
+
+pub async fn delete_user_role_by_user_and_role(
+    user: i64,
+    role: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!(
+        "DELETE FROM user_roles WHERE user == ? AND role == ?",
+        user,
+        role
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_user_roles_before(timestamp: NaiveDateTime, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM user_roles WHERE timestamp < ?", timestamp)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
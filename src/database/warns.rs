@@ -1,24 +1,32 @@
 use anyhow::Result;
+use chrono::NaiveDateTime;
 use sqlx::SqlitePool;
 
 pub struct DbWarn {
+    pub id: i64,
     pub user: i64,
     pub moderator: i64,
     pub reason: String,
     pub dm: bool,
     pub message: Option<String>,
+    pub timestamp: NaiveDateTime,
+    pub severity: String,
+    pub expires: Option<NaiveDateTime>,
 }
 
 pub async fn set_warn(warn: DbWarn, pool: &SqlitePool) -> Result<i64> {
     let mut transaction = pool.begin().await?;
 
     sqlx::query!(
-        "INSERT OR REPLACE INTO warns(user, moderator, reason, dm, message) VALUES(?, ?, ?, ?, ?)",
+        "INSERT OR REPLACE INTO warns(user, moderator, reason, dm, message, timestamp, severity, expires) VALUES(?, ?, ?, ?, ?, ?, ?, ?)",
         warn.user,
         warn.moderator,
         warn.reason,
         warn.dm,
         warn.message,
+        warn.timestamp,
+        warn.severity,
+        warn.expires,
     )
     .execute(&mut *transaction)
     .await?;
@@ -32,3 +40,74 @@ pub async fn set_warn(warn: DbWarn, pool: &SqlitePool) -> Result<i64> {
 
     Ok(id as i64)
 }
+
+pub async fn get_warn_by_id(id: i64, pool: &SqlitePool) -> Result<DbWarn> {
+    Ok(
+        sqlx::query_as!(DbWarn, "SELECT * FROM warns WHERE id = ?", id)
+            .fetch_one(pool)
+            .await?,
+    )
+}
+
+pub async fn get_warns_by_user(user: i64, pool: &SqlitePool) -> Result<Vec<DbWarn>> {
+    Ok(sqlx::query_as!(
+        DbWarn,
+        "SELECT * FROM warns WHERE user = ? ORDER BY id DESC",
+        user
+    )
+    .fetch_all(pool)
+    .await?)
+}
+
+pub async fn get_warns_since(since: NaiveDateTime, pool: &SqlitePool) -> Result<Vec<DbWarn>> {
+    Ok(
+        sqlx::query_as!(DbWarn, "SELECT * FROM warns WHERE timestamp >= ?", since)
+            .fetch_all(pool)
+            .await?,
+    )
+}
+
+pub async fn get_active_warn_count_by_user_since(
+    user: i64,
+    since: NaiveDateTime,
+    now: NaiveDateTime,
+    pool: &SqlitePool,
+) -> Result<i64> {
+    Ok(sqlx::query!(
+        r#"SELECT COUNT(*) as "count: i64" FROM warns WHERE user = ? AND timestamp >= ? AND (expires IS NULL OR expires > ?)"#,
+        user,
+        since,
+        now
+    )
+    .fetch_one(pool)
+    .await?
+    .count)
+}
+
+pub async fn update_warn_reason_by_id(id: i64, reason: &str, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("UPDATE warns SET reason = ? WHERE id = ?", reason, id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn update_warn_expires_by_id(
+    id: i64,
+    expires: NaiveDateTime,
+    pool: &SqlitePool,
+) -> Result<()> {
+    sqlx::query!("UPDATE warns SET expires = ? WHERE id = ?", expires, id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn delete_warn_by_id(id: i64, pool: &SqlitePool) -> Result<()> {
+    sqlx::query!("DELETE FROM warns WHERE id = ?", id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
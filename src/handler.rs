@@ -1,16 +1,19 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::Arc,
+};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::Utc;
 use linked_hash_map::LinkedHashMap;
 use serenity::{
     all::{
-        Channel, ChannelType, Command, CommandInteraction, ComponentInteraction, GuildChannel,
-        GuildId, Interaction, Member, Message, ModalInteraction, Reaction, Ready, RoleId, User,
-        UserId,
+        Channel, ChannelId, ChannelType, Command, CommandInteraction, ComponentInteraction,
+        GuildId, GuildMemberUpdateEvent, Interaction, Member, Message, MessageId,
+        MessageUpdateEvent, ModalInteraction, Reaction, Ready, RoleId, User, UserId,
     },
     builder::CreateInteractionResponseFollowup,
     client::{Context, EventHandler},
-    futures::StreamExt,
     gateway::ActivityData,
     prelude::TypeMapKey,
 };
@@ -18,7 +21,7 @@ use sqlx::SqlitePool;
 use strum::IntoEnumIterator;
 use tokio::sync::Mutex;
 
-use crate::{database, listener, GUILD_ID};
+use crate::{database, listener, updater, GUILD_ID};
 
 pub struct MessageCache;
 
@@ -26,6 +29,323 @@ impl TypeMapKey for MessageCache {
     type Value = Arc<Mutex<LinkedHashMap<(u64, u64), Message>>>;
 }
 
+pub struct RaidState;
+
+impl TypeMapKey for RaidState {
+    type Value = Arc<Mutex<Raid>>;
+}
+
+pub struct AutomodRegexes;
+
+impl TypeMapKey for AutomodRegexes {
+    /// Compiled automod patterns by their source
+    type Value = Arc<Mutex<HashMap<String, regex::Regex>>>;
+}
+
+#[derive(Default)]
+pub struct Raid {
+    /// Join timestamps of the last minute
+    pub joins: VecDeque<i64>,
+    /// Timestamp raid mode ends at
+    pub until: i64,
+}
+
+pub async fn get_cached_message(
+    ctx: &Context,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    pool: &SqlitePool,
+) -> Result<Option<Message>> {
+    let message_cache_lock = {
+        let data = ctx.data.read().await;
+
+        data.get::<MessageCache>().unwrap().clone()
+    };
+
+    if let Some(message) = message_cache_lock
+        .lock()
+        .await
+        .get(&(channel_id.get(), message_id.get()))
+    {
+        return Ok(Some(message.clone()));
+    }
+
+    Ok(database::get_cached_message_by_channel_and_message(
+        channel_id.get() as i64,
+        message_id.get() as i64,
+        pool,
+    )
+    .await?
+    .and_then(|m| serde_json::from_str(&m.data).ok()))
+}
+
+pub async fn get_cached_messages_by_author(ctx: &Context, user: UserId) -> Vec<Message> {
+    let message_cache_lock = {
+        let data = ctx.data.read().await;
+
+        data.get::<MessageCache>().unwrap().clone()
+    };
+
+    let message_cache = message_cache_lock.lock().await;
+
+    message_cache
+        .values()
+        .filter(|m| m.author.id == user)
+        .cloned()
+        .collect()
+}
+
+async fn cache_message(ctx: &Context, message: Message, pool: &SqlitePool) -> Result<()> {
+    // Runs on every message, so the channel limit is enforced by the message cache updater
+    if updater::message_cache_persistent() {
+        let cached_message = database::DbCachedMessage {
+            channel: message.channel_id.get() as i64,
+            message: message.id.get() as i64,
+            data: serde_json::to_string(&message)?,
+            timestamp: message.timestamp.naive_utc(),
+        };
+        database::set_cached_message(&cached_message, pool).await?;
+    }
+
+    let message_cache_lock = {
+        let data = ctx.data.read().await;
+
+        data.get::<MessageCache>().unwrap().clone()
+    };
+
+    let mut message_cache = message_cache_lock.lock().await;
+    message_cache.insert((message.channel_id.get(), message.id.get()), message);
+    if message_cache.len() > 1000 {
+        message_cache.pop_front();
+    }
+
+    Ok(())
+}
+
+/// Gives a member the base role and restores the roles they had when they left
+pub async fn add_member_roles(ctx: &Context, member: &Member, pool: &SqlitePool) -> Result<()> {
+    let guild_roles = member.guild_id.roles(ctx).await?;
+
+    let position = {
+        let user = ctx.cache.current_user().id;
+        let member = member.guild_id.member(ctx, user).await?;
+
+        member
+            .roles
+            .iter()
+            .map(|r| guild_roles[r].position)
+            .max()
+            .unwrap()
+    };
+
+    let exclusions = database::get_persist_exclusions(pool)
+        .await?
+        .into_iter()
+        .map(|e| e.role)
+        .collect::<HashSet<_>>();
+
+    let mut roles = vec![1210489410467143741];
+
+    for user_role in database::get_user_roles_by_user(member.user.id.get() as i64, pool).await? {
+        if exclusions.contains(&user_role.role) {
+            continue;
+        }
+
+        // Managed roles belong to integrations and can't be given by the bot
+        if guild_roles
+            .get(&RoleId::new(user_role.role as u64))
+            .map(|r| !r.managed && r.position < position)
+            .unwrap_or_default()
+        {
+            roles.push(user_role.role as u64);
+        }
+    }
+
+    let mut failed = Vec::new();
+
+    for role in roles {
+        if let Err(e) = updater::add_role(&ctx.http, member, RoleId::new(role)).await {
+            // A role the bot isn't allowed to give won't work on the next try either
+            if e.downcast_ref().is_some_and(updater::forbidden) {
+                database::delete_user_role_by_user_and_role(
+                    member.user.id.get() as i64,
+                    role as i64,
+                    pool,
+                )
+                .await?;
+            }
+
+            failed.push(format!("<@&{role}> ({e})"));
+        }
+    }
+
+    // Saved roles are kept on failure so the next reconciliation tries again
+    if !failed.is_empty() {
+        return Err(anyhow!("Couldn't add {}", failed.join(", ")));
+    }
+
+    database::delete_user_roles_by_user(member.user.id.get() as i64, pool).await?;
+
+    Ok(())
+}
+
+/// Saves the roles of a member who left so they can be restored when they rejoin
+async fn save_user_roles(ctx: &Context, user: i64, roles: &[i64], pool: &SqlitePool) -> Result<()> {
+    let mut exclusions = database::get_persist_exclusions(pool)
+        .await?
+        .into_iter()
+        .map(|e| e.role)
+        .collect::<HashSet<_>>();
+    exclusions.extend(managed_roles(ctx));
+
+    database::delete_user_roles_by_user(user, pool).await?;
+
+    let timestamp = Utc::now().naive_utc();
+
+    for role in roles.iter().filter(|r| !exclusions.contains(r)) {
+        let user_role = database::DbUserRole {
+            user,
+            role: *role,
+            timestamp,
+        };
+        database::set_user_role(&user_role, pool).await?;
+    }
+
+    database::delete_member_roles_by_user(user, pool).await?;
+
+    Ok(())
+}
+
+/// Roles managed by integrations, which can't be given to members
+fn managed_roles(ctx: &Context) -> HashSet<i64> {
+    ctx.cache
+        .guild(GUILD_ID)
+        .map(|guild| {
+            guild
+                .roles
+                .values()
+                .filter(|r| r.managed)
+                .map(|r| r.id.get() as i64)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+async fn set_member_roles(user: i64, roles: &[RoleId], pool: &SqlitePool) -> Result<()> {
+    database::delete_member_roles_by_user(user, pool).await?;
+
+    for role in roles {
+        let member_role = database::DbMemberRole {
+            user,
+            role: role.get() as i64,
+        };
+        database::set_member_role(&member_role, pool).await?;
+    }
+
+    Ok(())
+}
+
+/// Catches up on members who left or rejoined while the bot was offline
+async fn reconcile_user_roles(ctx: &Context, pool: &SqlitePool) -> Result<()> {
+    let mut members = Vec::new();
+    let mut after = None;
+
+    loop {
+        let page = GUILD_ID.members(ctx, Some(1000), after).await?;
+
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.user.id);
+
+        members.extend(page);
+    }
+
+    let present = members
+        .iter()
+        .map(|m| m.user.id.get() as i64)
+        .collect::<HashSet<_>>();
+
+    let mut left: HashMap<i64, Vec<i64>> = HashMap::new();
+    for member_role in database::get_member_roles(pool).await? {
+        if !present.contains(&member_role.user) {
+            left.entry(member_role.user)
+                .or_default()
+                .push(member_role.role);
+        }
+    }
+
+    for (user, roles) in &left {
+        save_user_roles(ctx, *user, roles, pool).await?;
+    }
+
+    let saved = database::get_user_roles(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.user)
+        .collect::<HashSet<_>>();
+    let held = database::get_held_members(pool)
+        .await?
+        .into_iter()
+        .map(|m| m.user)
+        .collect::<HashSet<_>>();
+
+    let mut rejoined = 0;
+    let mut failures = Vec::new();
+    for member in &members {
+        let user = member.user.id.get() as i64;
+
+        if member.user.bot || !saved.contains(&user) || held.contains(&user) {
+            continue;
+        }
+
+        if let Err(e) = add_member_roles(ctx, member, pool).await {
+            failures.push(format!("<@{user}> - {e}"));
+            continue;
+        }
+
+        rejoined += 1;
+    }
+
+    let member_roles = members
+        .iter()
+        .filter(|m| !m.user.bot)
+        .flat_map(|m| {
+            m.roles.iter().map(|r| database::DbMemberRole {
+                user: m.user.id.get() as i64,
+                role: r.get() as i64,
+            })
+        })
+        .collect::<Vec<_>>();
+    database::set_member_roles(&member_roles, pool).await?;
+
+    let mut summary = format!(
+        "Reconciled roles: saved roles of {} members who left, restored roles of {rejoined} members who rejoined, {} failed",
+        left.len(),
+        failures.len()
+    );
+
+    if !failures.is_empty() {
+        summary.push_str(" <@246684413075652612>\n");
+        summary.push_str(
+            &failures
+                .iter()
+                .take(20)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        if failures.len() > 20 {
+            summary.push_str(&format!("\nAnd {} more", failures.len() - 20));
+        }
+    }
+
+    updater::log(&summary, &ctx.http).await;
+
+    Ok(())
+}
+
 pub struct Handler {
     pub user: Arc<Mutex<UserId>>,
     pub pool: SqlitePool,
@@ -42,7 +362,14 @@ impl Handler {
     }
 
     async fn component(&self, ctx: &Context, interaction: &ComponentInteraction) -> Result<()> {
-        if let Some(listener) = self.listeners.get(interaction.data.custom_id.as_str()) {
+        let name = interaction
+            .data
+            .custom_id
+            .split(':')
+            .next()
+            .unwrap_or_default();
+
+        if let Some(listener) = self.listeners.get(name) {
             return listener.component(ctx, interaction, &self.pool).await;
         }
 
@@ -50,7 +377,14 @@ impl Handler {
     }
 
     async fn modal(&self, ctx: &Context, interaction: &ModalInteraction) -> Result<()> {
-        if let Some(listener) = self.listeners.get(interaction.data.custom_id.as_str()) {
+        let name = interaction
+            .data
+            .custom_id
+            .split(':')
+            .next()
+            .unwrap_or_default();
+
+        if let Some(listener) = self.listeners.get(name) {
             return listener.modal(ctx, interaction, &self.pool).await;
         }
 
@@ -73,6 +407,77 @@ impl Handler {
         Ok(())
     }
 
+    async fn message(&self, ctx: &Context, message: &Message) {
+        // Runs on every message, so one failing listener must not stop the others or the caching
+        for listener in self.listeners.values() {
+            if let Err(e) = listener.message(ctx, message, &self.pool).await {
+                updater::log(&format!("Error: {listener} message {e}"), &ctx.http).await;
+            }
+        }
+    }
+
+    async fn member_addition(&self, ctx: &Context, member: &Member) {
+        // The hold check and role restoration still have to run after a failing listener
+        for listener in self.listeners.values() {
+            if let Err(e) = listener.member_addition(ctx, member, &self.pool).await {
+                updater::log(&format!("Error: {listener} member addition {e}"), &ctx.http).await;
+            }
+        }
+    }
+
+    async fn welcome(&self, ctx: &Context, member: &Member) -> Result<()> {
+        for listener in self.listeners.values() {
+            listener.welcome(ctx, member, &self.pool).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn member_update(
+        &self,
+        ctx: &Context,
+        old: Option<Member>,
+        event: &GuildMemberUpdateEvent,
+    ) -> Result<()> {
+        let old_roles = match old {
+            Some(member) => member.roles,
+            None => database::get_member_roles_by_user(event.user.id.get() as i64, &self.pool)
+                .await?
+                .into_iter()
+                .map(|r| RoleId::new(r.role as u64))
+                .collect(),
+        };
+
+        for listener in self.listeners.values() {
+            listener
+                .member_update(ctx, &old_roles, event, &self.pool)
+                .await?;
+        }
+
+        set_member_roles(event.user.id.get() as i64, &event.roles, &self.pool).await
+    }
+
+    async fn hold_member(&self, ctx: &Context, member: &Member) -> bool {
+        for listener in self.listeners.values() {
+            match listener.hold_member(ctx, member, &self.pool).await {
+                Ok(true) => return true,
+                Ok(false) => {}
+                Err(e) => {
+                    updater::log(
+                        &format!(
+                            "Error: {listener} hold member <@{}> {e} <@246684413075652612>",
+                            member.user.id
+                        ),
+                        &ctx.http,
+                    )
+                    .await
+                }
+            }
+        }
+
+        false
+    }
+
     async fn ban_add(&self, ctx: &Context, guild: &GuildId, user: &User) -> Result<()> {
         for listener in self.listeners.values() {
             listener.ban_add(ctx, guild, user, &self.pool).await?;
@@ -88,6 +493,40 @@ impl Handler {
 
         Ok(())
     }
+
+    async fn message_update(&self, ctx: &Context, event: &MessageUpdateEvent) -> Result<()> {
+        let old = get_cached_message(ctx, event.channel_id, event.id, &self.pool).await?;
+
+        if let Some(mut message) = old.clone() {
+            event.apply_to_message(&mut message);
+            cache_message(ctx, message, &self.pool).await?;
+        }
+
+        for listener in self.listeners.values() {
+            listener
+                .message_update(ctx, old.as_ref(), event, &self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: &Context,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+    ) -> Result<()> {
+        let old = get_cached_message(ctx, *channel_id, *message_id, &self.pool).await?;
+
+        for listener in self.listeners.values() {
+            listener
+                .message_delete(ctx, channel_id, message_id, old.as_ref(), &self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
 }
 
 #[serenity::async_trait]
@@ -99,6 +538,8 @@ impl EventHandler for Handler {
             let mut data = ctx.data.write().await;
 
             data.insert::<MessageCache>(Default::default());
+            data.insert::<RaidState>(Default::default());
+            data.insert::<AutomodRegexes>(Default::default());
         }
 
         let mut commands = Vec::new();
@@ -109,27 +550,24 @@ impl EventHandler for Handler {
 
         ctx.set_activity(Some(ActivityData::watching("Chive Hunters")));
 
+        let pool = self.pool.clone();
+
         tokio::spawn(async move {
-            let members = GUILD_ID
-                .members_iter(&ctx)
-                .filter_map(|m| async move {
-                    m.ok().and_then(|m| {
-                        (!(m.roles.contains(&RoleId::new(1210489410467143741)) || m.user.bot))
-                            .then_some(m)
-                    })
-                })
-                .collect::<Vec<_>>()
+            if let Err(e) = reconcile_user_roles(&ctx, &pool).await {
+                updater::log(
+                    &format!("Error: Role reconciliation {e} <@246684413075652612>"),
+                    &ctx.http,
+                )
                 .await;
-
-            for member in members {
-                loop {
-                    if member.add_role(&ctx, 1210489410467143741).await.is_ok() {
-                        break;
-                    }
-                }
             }
 
-            crate::updater::log("<@246684413075652612> Done", &ctx.http).await;
+            if let Err(e) = updater::backfill_base_role(&ctx.http, &pool).await {
+                updater::log(
+                    &format!("Error: Base role backfill {e} <@246684413075652612>"),
+                    &ctx.http,
+                )
+                .await;
+            }
         });
     }
 
@@ -189,18 +627,78 @@ impl EventHandler for Handler {
                     let _ = message.crosspost(&ctx).await;
                 }
             }
+
+            if let Err(e) = database::set_match_activity(
+                message.channel_id.get() as i64,
+                message.author.id.get() as i64,
+                message.timestamp.naive_utc(),
+                &self.pool,
+            )
+            .await
+            {
+                updater::log(&format!("Error: match activity {e}"), &ctx.http).await;
+            }
         }
 
-        let message_cache_lock = {
-            let data = ctx.data.read().await;
+        if message.guild_id == Some(GUILD_ID) {
+            self.message(&ctx, &message).await;
+        }
 
-            data.get::<MessageCache>().unwrap().clone()
-        };
+        if let Err(e) = cache_message(&ctx, message, &self.pool).await {
+            updater::log(&format!("Error: message cache {e}"), &ctx.http).await;
+        }
+    }
+
+    async fn message_update(
+        &self,
+        ctx: Context,
+        _: Option<Message>,
+        _: Option<Message>,
+        event: MessageUpdateEvent,
+    ) {
+        if event.guild_id != Some(GUILD_ID) {
+            return;
+        }
+
+        if let Err(e) = self.message_update(&ctx, &event).await {
+            updater::log(&format!("Error: message update {e}"), &ctx.http).await;
+        }
+    }
+
+    async fn message_delete(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        deleted_message_id: MessageId,
+        guild_id: Option<GuildId>,
+    ) {
+        if guild_id != Some(GUILD_ID) {
+            return;
+        }
 
-        let mut message_cache = message_cache_lock.lock().await;
-        message_cache.insert((message.channel_id.get(), message.id.get()), message);
-        if message_cache.len() > 1000 {
-            message_cache.pop_front();
+        if let Err(e) = self
+            .message_delete(&ctx, &channel_id, &deleted_message_id)
+            .await
+        {
+            updater::log(&format!("Error: message delete {e}"), &ctx.http).await;
+        }
+    }
+
+    async fn message_delete_bulk(
+        &self,
+        ctx: Context,
+        channel_id: ChannelId,
+        multiple_deleted_messages_ids: Vec<MessageId>,
+        guild_id: Option<GuildId>,
+    ) {
+        if guild_id != Some(GUILD_ID) {
+            return;
+        }
+
+        for message_id in &multiple_deleted_messages_ids {
+            if let Err(e) = self.message_delete(&ctx, &channel_id, message_id).await {
+                updater::log(&format!("Error: message delete {e}"), &ctx.http).await;
+            }
         }
     }
 
@@ -213,71 +711,68 @@ impl EventHandler for Handler {
             return;
         }
 
-        let guild_roles = member.guild_id.roles(&ctx).await.unwrap();
-
-        let position = {
-            let user = *self.user.lock().await;
-            let member = member.guild_id.member(&ctx, user).await.unwrap();
-
-            member
-                .roles
-                .iter()
-                .map(|r| guild_roles[r].position)
-                .max()
-                .unwrap()
-        };
-
-        let mut roles = vec![1210489410467143741];
+        // Punishments apply even to members held for review
+        self.member_addition(&ctx, &member).await;
 
-        for user_role in database::get_user_roles_by_user(member.user.id.get() as i64, &self.pool)
-            .await
-            .unwrap()
-        {
-            if guild_roles
-                .get(&RoleId::new(user_role.role as u64))
-                .map(|r| r.position < position)
-                .unwrap_or_default()
-            {
-                roles.push(user_role.role as u64);
-            }
+        if self.hold_member(&ctx, &member).await {
+            return;
         }
 
-        for role in roles {
-            loop {
-                if member.add_role(&ctx, role).await.is_ok() {
-                    break;
-                }
-            }
+        if let Err(e) = add_member_roles(&ctx, &member, &self.pool).await {
+            updater::log(
+                &format!(
+                    "Error: Roles of <@{}> {e} <@246684413075652612>",
+                    member.user.id
+                ),
+                &ctx.http,
+            )
+            .await;
         }
 
-        database::delete_user_roles_by_user(member.user.id.get() as i64, &self.pool)
-            .await
-            .unwrap();
+        self.welcome(&ctx, &member).await.unwrap();
     }
 
     async fn guild_member_removal(
         &self,
-        _: Context,
+        ctx: Context,
         _: GuildId,
         user: User,
         member_data_if_available: Option<Member>,
     ) {
-        let Some(member) = member_data_if_available else {
-            return;
+        let roles = match member_data_if_available {
+            Some(member) => member.roles.iter().map(|r| r.get() as i64).collect(),
+            // Fall back to the snapshot when the member wasn't cached
+            None => database::get_member_roles_by_user(user.id.get() as i64, &self.pool)
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|r| r.role)
+                .collect::<Vec<_>>(),
         };
 
-        let mut user_role = database::DbUserRole {
-            user: user.id.get() as i64,
-            role: 0,
-        };
+        save_user_roles(&ctx, user.id.get() as i64, &roles, &self.pool)
+            .await
+            .unwrap();
 
-        for role in member.roles {
-            user_role.role = role.get() as i64;
+        database::delete_held_member_by_user(user.id.get() as i64, &self.pool)
+            .await
+            .unwrap();
+    }
 
-            database::set_user_role(&user_role, &self.pool)
-                .await
-                .unwrap();
+    async fn guild_member_update(
+        &self,
+        ctx: Context,
+        old_if_available: Option<Member>,
+        _: Option<Member>,
+        event: GuildMemberUpdateEvent,
+    ) {
+        if event.guild_id != GUILD_ID || event.user.bot {
+            return;
         }
+
+        self.member_update(&ctx, old_if_available, &event)
+            .await
+            .unwrap();
     }
 
     async fn guild_ban_addition(&self, ctx: Context, guild: GuildId, user: User) {
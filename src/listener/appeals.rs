@@ -0,0 +1,484 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serenity::{
+    all::{
+        ActionRowComponent, ButtonStyle, ChannelId, CommandInteraction, ComponentInteraction,
+        CreateInputText, CreateModal, InputTextStyle, ModalInteraction, UserId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        EditInteractionResponse, EditMember,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+    GUILD_ID,
+};
+
+use super::{case::CaseKind, modstats::UNBAN};
+
+const ACCEPT_ID: &str = "accept";
+const DENY_ID: &str = "deny";
+
+const PENDING: &str = "pending";
+const ACCEPTED: &str = "accepted";
+const DENIED: &str = "denied";
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum AppealKind {
+    Warn,
+    Case,
+}
+
+pub fn button(kind: AppealKind, id: i64) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(format!(
+        "{}:{kind}:{id}",
+        super::ListenerName::Appeals
+    ))
+    .label("Appeal")
+    .style(ButtonStyle::Secondary)])
+}
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Pending appeals")
+            .default_member_permissions(Permissions::MANAGE_NICKNAMES)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let appeals = database::get_appeals_by_status(PENDING, pool).await?;
+
+    if appeals.is_empty() {
+        return Err(anyhow!("There are no pending appeals"));
+    }
+
+    let message = appeals
+        .iter()
+        .take(25)
+        .map(|a| {
+            let link = match (a.channel, a.message) {
+                (Some(channel), Some(message)) => {
+                    format!(" - https://discord.com/channels/{GUILD_ID}/{channel}/{message}")
+                }
+                _ => String::new(),
+            };
+
+            format!(
+                "Appeal {} - <@{}> - {} {}{link}",
+                a.id, a.user, a.kind, a.reference
+            )
+        })
+        .collect::<Vec<_>>();
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title("Pending appeals")
+                        .description(message.join("\n")),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let action = parts.next().ok_or_else(|| anyhow!("No action"))?;
+    let id: i64 = parts.next().ok_or_else(|| anyhow!("No id"))?.parse()?;
+
+    match action {
+        ACCEPT_ID => accept(ctx, interaction, id, pool).await,
+        DENY_ID => {
+            let appeal = database::get_appeal_by_id(id, pool).await?;
+
+            if appeal.status != PENDING {
+                return respond(ctx, interaction, "This appeal has already been handled").await;
+            }
+
+            interaction
+                .create_response(
+                    &ctx,
+                    CreateInteractionResponse::Modal(
+                        CreateModal::new(
+                            format!("{}:{DENY_ID}:{id}", super::ListenerName::Appeals),
+                            format!("Deny appeal {id}"),
+                        )
+                        .components(vec![CreateActionRow::InputText(
+                            CreateInputText::new(InputTextStyle::Paragraph, "Reason", "reason")
+                                .placeholder("Why the appeal is denied"),
+                        )]),
+                    ),
+                )
+                .await?;
+
+            Ok(())
+        }
+        kind => {
+            let kind = AppealKind::from_str(kind).map_err(|_| anyhow!("Unknown appeal"))?;
+
+            if database::get_appeal_by_kind_and_reference(&kind.to_string(), id, pool)
+                .await?
+                .is_some()
+            {
+                return respond(ctx, interaction, "You have already appealed this").await;
+            }
+
+            interaction
+                .create_response(
+                    &ctx,
+                    CreateInteractionResponse::Modal(
+                        CreateModal::new(interaction.data.custom_id.clone(), "Appeal").components(
+                            vec![CreateActionRow::InputText(
+                                CreateInputText::new(
+                                    InputTextStyle::Paragraph,
+                                    "Why should this be revoked?",
+                                    "content",
+                                )
+                                .max_length(1000),
+                            )],
+                        ),
+                    ),
+                )
+                .await?;
+
+            Ok(())
+        }
+    }
+}
+
+pub async fn modal(ctx: &Context, interaction: &ModalInteraction, pool: &SqlitePool) -> Result<()> {
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let action = parts.next().ok_or_else(|| anyhow!("No action"))?;
+    let id: i64 = parts.next().ok_or_else(|| anyhow!("No id"))?.parse()?;
+
+    let input = interaction
+        .data
+        .components
+        .iter()
+        .flat_map(|r| &r.components)
+        .find_map(|c| match c {
+            ActionRowComponent::InputText(i) => i.value.clone(),
+            _ => None,
+        })
+        .unwrap_or_default();
+
+    match action {
+        DENY_ID => deny(ctx, interaction, id, &input, pool).await,
+        kind => {
+            let kind = AppealKind::from_str(kind).map_err(|_| anyhow!("Unknown appeal"))?;
+            submit(ctx, interaction, kind, id, input, pool).await
+        }
+    }
+}
+
+async fn submit(
+    ctx: &Context,
+    interaction: &ModalInteraction,
+    kind: AppealKind,
+    reference: i64,
+    content: String,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction.defer_ephemeral(&ctx).await?;
+
+    let (user, _) = subject(kind, reference, pool).await?;
+
+    if user != interaction.user.id.get() as i64 {
+        return Err(anyhow!("You can only appeal your own punishments"));
+    }
+
+    if database::get_appeal_by_kind_and_reference(&kind.to_string(), reference, pool)
+        .await?
+        .is_some()
+    {
+        return Err(anyhow!("You have already appealed this"));
+    }
+
+    let channel: u64 = settings::get(Setting::AppealChannel, pool).await?;
+
+    if channel == 0 {
+        return Err(anyhow!("Appeals are currently not being accepted"));
+    }
+
+    let mut appeal = database::DbAppeal {
+        id: 0,
+        kind: kind.to_string(),
+        reference,
+        user,
+        content,
+        status: PENDING.to_string(),
+        moderator: None,
+        response: None,
+        channel: None,
+        message: None,
+        timestamp: Utc::now().naive_utc(),
+    };
+    appeal.id = database::set_appeal(&appeal, pool).await?;
+
+    let name = super::ListenerName::Appeals.to_string();
+    let channel = ChannelId::new(channel);
+
+    let message = channel
+        .send_message(
+            &ctx,
+            CreateMessage::new()
+                .embed(appeal_embed(&appeal, pool).await?)
+                .components(vec![CreateActionRow::Buttons(vec![
+                    CreateButton::new(format!("{name}:{ACCEPT_ID}:{}", appeal.id))
+                        .label("Accept")
+                        .style(ButtonStyle::Success),
+                    CreateButton::new(format!("{name}:{DENY_ID}:{}", appeal.id))
+                        .label("Deny")
+                        .style(ButtonStyle::Danger),
+                ])]),
+        )
+        .await?;
+
+    database::update_appeal_message_by_id(
+        appeal.id,
+        channel.get() as i64,
+        message.id.get() as i64,
+        pool,
+    )
+    .await?;
+
+    interaction
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content("Your appeal has been submitted. You will be notified once it has been reviewed.")
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn accept(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    id: i64,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let appeal = database::get_appeal_by_id(id, pool).await?;
+
+    if appeal.status != PENDING {
+        return Err(anyhow!("This appeal has already been handled"));
+    }
+
+    let user = UserId::new(appeal.user as u64);
+    let moderator = interaction.user.id.get() as i64;
+    let now = Utc::now().naive_utc();
+
+    match AppealKind::from_str(&appeal.kind)? {
+        AppealKind::Warn => {
+            database::update_warn_expires_by_id(appeal.reference, now, pool).await?
+        }
+        AppealKind::Case => {
+            let case = database::get_case_by_id(appeal.reference, pool).await?;
+
+            if case.kind == CaseKind::Ban.to_string() {
+                GUILD_ID.unban(&ctx, user).await?;
+
+                let db_case = database::DbCase {
+                    kind: UNBAN.to_string(),
+                    user: appeal.user,
+                    moderator,
+                    reason: format!("Appeal {id} accepted"),
+                    duration: None,
+                    dm: false,
+                    message: None,
+                    timestamp: now,
+                };
+                database::set_case(&db_case, pool).await?;
+            } else if case.kind == CaseKind::Timeout.to_string() {
+                GUILD_ID
+                    .edit_member(
+                        &ctx,
+                        user,
+                        EditMember::new()
+                            .enable_communication()
+                            .audit_log_reason(&format!("Appeal {id} accepted")),
+                    )
+                    .await?;
+            }
+        }
+    }
+
+    database::update_appeal_status_by_id(id, ACCEPTED, moderator, None, pool).await?;
+
+    notify(
+        ctx,
+        user,
+        &format!("Your appeal {id} has been accepted and the punishment has been revoked."),
+    )
+    .await;
+
+    let appeal = database::get_appeal_by_id(id, pool).await?;
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .embed(appeal_embed(&appeal, pool).await?)
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn deny(
+    ctx: &Context,
+    interaction: &ModalInteraction,
+    id: i64,
+    reason: &str,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let appeal = database::get_appeal_by_id(id, pool).await?;
+
+    if appeal.status != PENDING {
+        return Err(anyhow!("This appeal has already been handled"));
+    }
+
+    database::update_appeal_status_by_id(
+        id,
+        DENIED,
+        interaction.user.id.get() as i64,
+        Some(reason),
+        pool,
+    )
+    .await?;
+
+    notify(
+        ctx,
+        UserId::new(appeal.user as u64),
+        &format!("Your appeal {id} has been denied: {reason}"),
+    )
+    .await;
+
+    let appeal = database::get_appeal_by_id(id, pool).await?;
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .embed(appeal_embed(&appeal, pool).await?)
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn subject(kind: AppealKind, reference: i64, pool: &SqlitePool) -> Result<(i64, String)> {
+    match kind {
+        AppealKind::Warn => {
+            let warn = database::get_warn_by_id(reference, pool).await?;
+
+            Ok((warn.user, format!("Warning {reference}: {}", warn.reason)))
+        }
+        AppealKind::Case => {
+            let case = database::get_case_by_id(reference, pool).await?;
+
+            Ok((
+                case.user,
+                format!("Case {reference} ({}): {}", case.kind, case.reason),
+            ))
+        }
+    }
+}
+
+async fn appeal_embed(appeal: &database::DbAppeal, pool: &SqlitePool) -> Result<CreateEmbed> {
+    let (_, subject) = subject(AppealKind::from_str(&appeal.kind)?, appeal.reference, pool).await?;
+
+    let color = match appeal.status.as_str() {
+        ACCEPTED => 0x00ff00,
+        DENIED => 0xff0000,
+        _ => 0xffa500,
+    };
+
+    let mut embed = CreateEmbed::new()
+        .color(color)
+        .title(format!("Appeal {}", appeal.id))
+        .field("User", format!("<@{}>", appeal.user), true)
+        .field("Appealed", subject, true)
+        .field("Appeal", &appeal.content, false);
+
+    if let Some(moderator) = appeal.moderator {
+        let status = if appeal.status == ACCEPTED {
+            "Accepted"
+        } else {
+            "Denied"
+        };
+
+        embed = embed.field("Status", format!("{status} by <@{moderator}>"), true);
+    }
+
+    if let Some(response) = &appeal.response {
+        embed = embed.field("Response", response, true);
+    }
+
+    Ok(embed)
+}
+
+async fn notify(ctx: &Context, user: UserId, content: &str) {
+    if let Ok(channel) = user.create_dm_channel(&ctx).await {
+        let _ = channel
+            .send_message(&ctx, CreateMessage::new().content(content))
+            .await;
+    }
+}
+
+async fn respond(ctx: &Context, interaction: &ComponentInteraction, content: &str) -> Result<()> {
+    interaction
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -1,21 +1,35 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use anyhow::{anyhow, Result};
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use serenity::{
-    all::{CommandInteraction, ComponentInteraction},
+    all::{CommandInteraction, CommandOptionType, ComponentInteraction},
     builder::{
-        CreateCommand, CreateInteractionResponse, CreateInteractionResponseFollowup,
-        CreateInteractionResponseMessage,
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
     },
     client::Context,
 };
 use sqlx::SqlitePool;
 
-use crate::database;
+use crate::{database, updater};
+
+pub const STATUS_ID: &str = "status";
 
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
         CreateCommand::new(name)
-            .description("Start matching :D")
+            .description("Support matching")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "join",
+                "Start matching :D",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                STATUS_ID,
+                "Your position in the matching queue",
+            ))
             .dm_permission(false),
     );
 }
@@ -32,6 +46,19 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
 
     let user = command.user.id.get() as i64;
 
+    if command.data.options[0].name == STATUS_ID {
+        command
+            .create_followup(
+                &ctx,
+                CreateInteractionResponseFollowup::new()
+                    .embed(status(user, pool).await?)
+                    .ephemeral(true),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
     if database::get_connections_by_user(user, pool)
         .await?
         .is_empty()
@@ -52,7 +79,7 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
     let candidate = database::DbCandidate { user, timestamp };
     database::set_candidate(candidate, pool).await?;
 
-    command.create_followup(&ctx, CreateInteractionResponseFollowup::new().content("Successfully applied for support matching. You will be notified, once we have found a good partner for you :D").ephemeral(true)).await?;
+    command.create_followup(&ctx, CreateInteractionResponseFollowup::new().content("Successfully applied for support matching. You will be notified, once we have found a good partner for you :D\n\nCheck your position in the queue with /apply status").ephemeral(true)).await?;
 
     Ok(())
 }
@@ -73,6 +100,19 @@ pub async fn component(
 
     let user = interaction.user.id.get() as i64;
 
+    if interaction.data.custom_id.split(':').nth(1) == Some(STATUS_ID) {
+        interaction
+            .create_followup(
+                &ctx,
+                CreateInteractionResponseFollowup::new()
+                    .embed(status(user, pool).await?)
+                    .ephemeral(true),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
     if database::get_connections_by_user(user, pool)
         .await?
         .is_empty()
@@ -93,7 +133,97 @@ pub async fn component(
     let candidate = database::DbCandidate { user, timestamp };
     database::set_candidate(candidate, pool).await?;
 
-    interaction.create_followup(&ctx, CreateInteractionResponseFollowup::new().content("Successfully applied for support matching. You will be notified, once we have found a good partner for you :D").ephemeral(true)).await?;
+    interaction.create_followup(&ctx, CreateInteractionResponseFollowup::new().content("Successfully applied for support matching. You will be notified, once we have found a good partner for you :D\n\nCheck your position in the queue with /apply status").ephemeral(true)).await?;
 
     Ok(())
 }
+
+async fn status(user: i64, pool: &SqlitePool) -> Result<CreateEmbed> {
+    let mut queues: BTreeMap<i64, Vec<i64>> = BTreeMap::new();
+
+    for candidate in database::get_candidates(pool).await? {
+        let regions = database::get_connections_by_user(candidate.user, pool)
+            .await?
+            .iter()
+            .map(|c| c.uid / 100000000)
+            .collect::<BTreeSet<_>>();
+
+        for region in regions {
+            queues.entry(region).or_default().push(candidate.user);
+        }
+    }
+
+    let mut waits: BTreeMap<i64, Vec<Duration>> = BTreeMap::new();
+
+    let matched = database::get_match_histories(pool)
+        .await?
+        .into_iter()
+        .map(|h| (h.region, h.started, [h.applied1, h.applied2]))
+        .chain(
+            database::get_matches(pool)
+                .await?
+                .into_iter()
+                .map(|m| (m.region, m.timestamp, [m.applied1, m.applied2])),
+        );
+
+    for (region, started, applied) in matched {
+        for applied in applied.into_iter().flatten() {
+            waits.entry(region).or_default().push(started - applied);
+        }
+    }
+
+    let description = if let Ok(db_match) = database::get_match_by_user(user, pool).await {
+        format!("You are already in a match: <#{}>", db_match.channel)
+    } else if database::get_candidate_by_user(user, pool).await.is_ok() {
+        queues
+            .iter()
+            .filter_map(|(region, users)| {
+                users.iter().position(|&u| u == user).map(|i| {
+                    format!(
+                        "You are **#{}** of {} in the {} queue",
+                        i + 1,
+                        users.len(),
+                        updater::region_name(*region)
+                    )
+                })
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        "You are not matching".to_string()
+    };
+
+    let regions = queues.keys().chain(waits.keys()).collect::<BTreeSet<_>>();
+
+    let fields = regions.into_iter().map(|region| {
+        let waiting = queues.get(region).map(|u| u.len()).unwrap_or_default();
+
+        let average = waits
+            .get(region)
+            .map(|w| format_duration(w.iter().sum::<Duration>() / w.len() as i32))
+            .unwrap_or("-".to_string());
+
+        (
+            updater::region_name(*region),
+            format!("Waiting: {waiting}\nAverage time to match: {average}"),
+            true,
+        )
+    });
+
+    Ok(CreateEmbed::new()
+        .title("Matching Queue")
+        .description(description)
+        .fields(fields))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+
+    if minutes >= 24 * 60 {
+        format!("{}d {}h", minutes / (24 * 60), minutes / 60 % 24)
+    } else if minutes >= 60 {
+        format!("{}h {}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
@@ -0,0 +1,591 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use regex::Regex;
+use serenity::{
+    all::{
+        ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+        CommandOptionType, Mentionable, Message, RoleId, UserId,
+    },
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+use strum::IntoEnumIterator;
+
+use crate::{
+    database,
+    handler::{self, AutomodRegexes},
+    settings::{self, Setting},
+    GUILD_ID,
+};
+
+use super::{
+    case::{self, CaseKind},
+    messagelog::truncate,
+    warn::{self, message_to_warned_message, Severity},
+};
+
+const CHANNEL_KIND: &str = "channel";
+const ROLE_KIND: &str = "role";
+const INVITE_PATTERN: &str = r"(?i)(discord\.gg|discord(app)?\.com/invite)/[\w-]+";
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum AutomodKind {
+    Keyword,
+    Regex,
+    Invite,
+    Mentions,
+    Spam,
+    Attachment,
+}
+
+impl AutomodKind {
+    /// Checks the value and brings it into the form stored in the database
+    fn parse_value(&self, value: Option<&str>) -> Result<String> {
+        let value = value.map(str::trim).unwrap_or_default();
+
+        match self {
+            AutomodKind::Keyword => {
+                if value.is_empty() {
+                    return Err(anyhow!("Keyword rules need a keyword"));
+                }
+
+                Ok(value.to_lowercase())
+            }
+            AutomodKind::Regex => {
+                Regex::new(value).map_err(|_| anyhow!("Invalid regex {value}"))?;
+
+                Ok(value.to_string())
+            }
+            AutomodKind::Invite => Ok(String::new()),
+            AutomodKind::Mentions => match value {
+                "" => Ok("5".to_string()),
+                value => match value.parse::<u64>() {
+                    Ok(limit) if limit > 0 => Ok(limit.to_string()),
+                    _ => Err(anyhow!("Mention limit has to be a positive number")),
+                },
+            },
+            AutomodKind::Spam => match value {
+                "" => Ok("3/30".to_string()),
+                value => parse_spam(value)
+                    .map(|(count, seconds)| format!("{count}/{seconds}"))
+                    .ok_or_else(|| anyhow!("Spam rules need a value like 3/30 (messages/seconds)")),
+            },
+            AutomodKind::Attachment => {
+                let extensions = value
+                    .split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                    .filter(|e| !e.is_empty())
+                    .collect::<Vec<_>>();
+
+                if extensions.is_empty() {
+                    return Err(anyhow!(
+                        "Attachment rules need file extensions like exe,scr"
+                    ));
+                }
+
+                Ok(extensions.join(","))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum AutomodAction {
+    Delete,
+    Warn,
+    Timeout,
+}
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    let mut kind =
+        CreateCommandOption::new(CommandOptionType::String, "kind", "Kind").required(true);
+    for k in AutomodKind::iter() {
+        kind = kind.add_string_choice(k.to_string(), k.to_string());
+    }
+
+    let mut action =
+        CreateCommandOption::new(CommandOptionType::String, "action", "Action").required(true);
+    for a in AutomodAction::iter() {
+        action = action.add_string_choice(a.to_string(), a.to_string());
+    }
+
+    commands.push(
+        CreateCommand::new(name)
+            .description("Automod")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "add", "Add a rule")
+                    .add_sub_option(kind)
+                    .add_sub_option(action)
+                    .add_sub_option(CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "value",
+                        "Keyword, regex, mention limit, messages/seconds or file extensions",
+                    ))
+                    .add_sub_option(
+                        CreateCommandOption::new(
+                            CommandOptionType::Integer,
+                            "duration",
+                            "Timeout duration in minutes (60)",
+                        )
+                        .min_int_value(1)
+                        .max_int_value(28 * 24 * 60),
+                    ),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "remove", "Remove a rule")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "id", "Rule id")
+                            .required(true),
+                    ),
+            )
+            .add_option(scope_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "exempt",
+                "Exempt a channel or role from a rule",
+            )))
+            .add_option(scope_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "unexempt",
+                "Remove an exemption from a rule",
+            )))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List rules",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+fn scope_options(subcommand: CreateCommandOption) -> CreateCommandOption {
+    subcommand
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "id", "Rule id").required(true),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "Channel",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Role,
+            "role",
+            "Role",
+        ))
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let content = match command.data.options[0].name.as_str() {
+        "add" => add(options, pool).await?,
+        "remove" => {
+            let id = options[0].value.as_i64().unwrap();
+
+            if database::get_automod_rule_by_id(id, pool).await?.is_none() {
+                return Err(anyhow!("Rule {id} doesn't exist"));
+            }
+
+            database::delete_automod_rule_by_id(id, pool).await?;
+
+            format!("Removed rule {id}")
+        }
+        "exempt" => exempt(options, true, pool).await?,
+        "unexempt" => exempt(options, false, pool).await?,
+        "list" => list(pool).await?,
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn add(options: &[CommandDataOption], pool: &SqlitePool) -> Result<String> {
+    let values = options
+        .iter()
+        .map(|o| (o.name.as_str(), &o.value))
+        .collect::<HashMap<_, _>>();
+
+    let kind = values["kind"]
+        .as_str()
+        .and_then(|k| k.parse::<AutomodKind>().ok())
+        .ok_or_else(|| anyhow!("Invalid kind"))?;
+    let action = values["action"]
+        .as_str()
+        .and_then(|a| a.parse::<AutomodAction>().ok())
+        .ok_or_else(|| anyhow!("Invalid action"))?;
+
+    let value = kind.parse_value(values.get("value").and_then(|v| v.as_str()))?;
+
+    let duration = match action {
+        AutomodAction::Timeout => Some(
+            values
+                .get("duration")
+                .and_then(|v| v.as_i64())
+                .unwrap_or(60),
+        ),
+        _ => None,
+    };
+
+    let rule = database::DbAutomodRule {
+        id: 0,
+        kind: kind.to_string(),
+        value,
+        action: action.to_string(),
+        duration,
+    };
+    let id = database::set_automod_rule(&rule, pool).await?;
+
+    Ok(format!("Added rule {id}: {}", describe(&rule)))
+}
+
+async fn exempt(options: &[CommandDataOption], add: bool, pool: &SqlitePool) -> Result<String> {
+    let id = options[0].value.as_i64().unwrap();
+
+    if database::get_automod_rule_by_id(id, pool).await?.is_none() {
+        return Err(anyhow!("Rule {id} doesn't exist"));
+    }
+
+    let mut exemptions = Vec::new();
+    for option in options.iter().skip(1) {
+        match option.name.as_str() {
+            "channel" => exemptions.push(database::DbAutomodExemption {
+                rule: id,
+                kind: CHANNEL_KIND.to_string(),
+                target: option.value.as_channel_id().unwrap().get() as i64,
+            }),
+            "role" => exemptions.push(database::DbAutomodExemption {
+                rule: id,
+                kind: ROLE_KIND.to_string(),
+                target: option.value.as_role_id().unwrap().get() as i64,
+            }),
+            _ => {}
+        }
+    }
+
+    if exemptions.is_empty() {
+        return Err(anyhow!("Specify a channel or a role"));
+    }
+
+    for exemption in &exemptions {
+        if add {
+            database::set_automod_exemption(exemption, pool).await?;
+        } else {
+            database::delete_automod_exemption(exemption, pool).await?;
+        }
+    }
+
+    Ok(if add {
+        format!("Added exemptions to rule {id}")
+    } else {
+        format!("Removed exemptions from rule {id}")
+    })
+}
+
+async fn list(pool: &SqlitePool) -> Result<String> {
+    let rules = database::get_automod_rules(pool).await?;
+    let exemptions = database::get_automod_exemptions(pool).await?;
+
+    if rules.is_empty() {
+        return Ok("No automod rules".to_string());
+    }
+
+    Ok(rules
+        .iter()
+        .map(|r| {
+            let exempt = exemptions
+                .iter()
+                .filter(|e| e.rule == r.id)
+                .map(|e| match e.kind.as_str() {
+                    CHANNEL_KIND => format!("<#{}>", e.target),
+                    _ => format!("<@&{}>", e.target),
+                })
+                .collect::<Vec<_>>();
+
+            if exempt.is_empty() {
+                format!("{} - {}", r.id, describe(r))
+            } else {
+                format!("{} - {} - exempt: {}", r.id, describe(r), exempt.join(", "))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn describe(rule: &database::DbAutomodRule) -> String {
+    let mut description = if rule.value.is_empty() {
+        rule.kind.clone()
+    } else {
+        format!("{} `{}`", rule.kind, rule.value)
+    };
+
+    description.push_str(&format!(" -> {}", rule.action));
+
+    if let Some(duration) = rule.duration {
+        description.push_str(&format!(" ({duration} minutes)"));
+    }
+
+    description
+}
+
+pub async fn message(ctx: &Context, message: &Message, pool: &SqlitePool) -> Result<()> {
+    if message.author.bot {
+        return Ok(());
+    }
+
+    let rules = database::get_automod_rules(pool).await?;
+
+    if rules.is_empty() {
+        return Ok(());
+    }
+
+    let roles = message
+        .member
+        .as_ref()
+        .map(|m| m.roles.clone())
+        .unwrap_or_default();
+
+    // Staff are never acted on, warnings from automod can escalate up to a ban
+    if staff(ctx, message.author.id, &roles) {
+        return Ok(());
+    }
+
+    let exemptions = database::get_automod_exemptions(pool).await?;
+
+    for rule in rules {
+        let exempt =
+            exemptions
+                .iter()
+                .filter(|e| e.rule == rule.id)
+                .any(|e| match e.kind.as_str() {
+                    CHANNEL_KIND => e.target == message.channel_id.get() as i64,
+                    ROLE_KIND => roles.iter().any(|r| e.target == r.get() as i64),
+                    _ => false,
+                });
+
+        if exempt {
+            continue;
+        }
+
+        if let Some(reason) = matches(ctx, &rule, message).await {
+            return enforce(ctx, &rule, &reason, message, pool).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns why the message breaks the rule, if it does
+async fn matches(
+    ctx: &Context,
+    rule: &database::DbAutomodRule,
+    message: &Message,
+) -> Option<String> {
+    let kind = rule.kind.parse::<AutomodKind>().ok()?;
+
+    match kind {
+        AutomodKind::Keyword => message
+            .content
+            .to_lowercase()
+            .contains(&rule.value)
+            .then(|| format!("Used the keyword \"{}\"", rule.value)),
+        AutomodKind::Regex => regex(ctx, &rule.value)
+            .await?
+            .is_match(&message.content)
+            .then(|| "Matched a blocked pattern".to_string()),
+        AutomodKind::Invite => regex(ctx, INVITE_PATTERN)
+            .await?
+            .is_match(&message.content)
+            .then(|| "Posted an invite link".to_string()),
+        AutomodKind::Mentions => {
+            let limit = rule.value.parse::<usize>().ok()?;
+            let mentions = message.mentions.len()
+                + message.mention_roles.len()
+                + usize::from(message.mention_everyone);
+
+            (mentions > limit).then(|| format!("Mentioned {mentions} users or roles"))
+        }
+        AutomodKind::Spam => {
+            let (count, seconds) = parse_spam(&rule.value)?;
+            let content = message.content.trim().to_lowercase();
+
+            if content.is_empty() {
+                return None;
+            }
+
+            let since = message.timestamp.unix_timestamp() - seconds;
+            let duplicates = handler::get_cached_messages_by_author(ctx, message.author.id)
+                .await
+                .iter()
+                .filter(|m| {
+                    m.id != message.id
+                        && m.timestamp.unix_timestamp() >= since
+                        && m.content.trim().to_lowercase() == content
+                })
+                .count()
+                + 1;
+
+            (duplicates >= count)
+                .then(|| format!("Sent the same message {duplicates} times in {seconds} seconds"))
+        }
+        AutomodKind::Attachment => {
+            let extensions = rule.value.split(',').collect::<Vec<_>>();
+
+            message
+                .attachments
+                .iter()
+                .find(|a| {
+                    a.filename
+                        .rsplit_once('.')
+                        .is_some_and(|(_, e)| extensions.contains(&e.to_lowercase().as_str()))
+                })
+                .map(|a| format!("Uploaded {}", a.filename))
+        }
+    }
+}
+
+fn staff(ctx: &Context, user: UserId, roles: &[RoleId]) -> bool {
+    let Some(guild) = GUILD_ID.to_guild_cached(&ctx) else {
+        return false;
+    };
+
+    guild.owner_id == user
+        || roles.iter().filter_map(|r| guild.roles.get(r)).any(|r| {
+            r.permissions
+                .intersects(Permissions::MANAGE_MESSAGES | Permissions::ADMINISTRATOR)
+        })
+}
+
+/// Compiles a pattern once and reuses it for later messages
+async fn regex(ctx: &Context, pattern: &str) -> Option<Regex> {
+    let regexes_lock = {
+        let data = ctx.data.read().await;
+
+        data.get::<AutomodRegexes>().unwrap().clone()
+    };
+
+    let mut regexes = regexes_lock.lock().await;
+
+    if let Some(regex) = regexes.get(pattern) {
+        return Some(regex.clone());
+    }
+
+    let regex = Regex::new(pattern).ok()?;
+    regexes.insert(pattern.to_string(), regex.clone());
+
+    Some(regex)
+}
+
+async fn enforce(
+    ctx: &Context,
+    rule: &database::DbAutomodRule,
+    reason: &str,
+    message: &Message,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let action = rule
+        .action
+        .parse::<AutomodAction>()
+        .unwrap_or(AutomodAction::Delete);
+    let reason = format!("Automod rule {}: {reason}", rule.id);
+    let bot = ctx.http.get_current_user().await?;
+
+    // Another listener might have removed the message already
+    let _ = message.delete(&ctx).await;
+
+    let result = match action {
+        AutomodAction::Delete => Ok(()),
+        AutomodAction::Warn => {
+            warn::warn(
+                ctx,
+                message.author.id,
+                &reason,
+                Severity::Minor,
+                Some(message_to_warned_message(message)),
+                &bot,
+                pool,
+            )
+            .await
+        }
+        AutomodAction::Timeout => {
+            let db_case = case::new_case(
+                CaseKind::Timeout,
+                message.author.id,
+                reason.clone(),
+                Some(rule.duration.unwrap_or(60)),
+                Some(message_to_warned_message(message)),
+                &bot,
+            );
+            case::execute(ctx, CaseKind::Timeout, db_case, &bot, pool).await
+        }
+    };
+
+    let log_channel: u64 = settings::get(Setting::AutomodLogChannel, pool).await?;
+
+    if log_channel == 0 {
+        return result;
+    }
+
+    let mut embed = CreateEmbed::new()
+        .color(0xffa500)
+        .title(format!("Automod rule {} triggered", rule.id))
+        .description(format!(
+            "{} in {}",
+            message.author.mention(),
+            message.channel_id.mention()
+        ))
+        .field("Rule", describe(rule), false)
+        .field("Reason", &reason, false)
+        .field("Content", truncate(&message.content), false)
+        .footer(CreateEmbedFooter::new(format!(
+            "User {}",
+            message.author.id
+        )));
+
+    if let Err(e) = result {
+        embed = embed.field("Action failed", e.to_string(), false);
+    }
+
+    ChannelId::new(log_channel)
+        .send_message(&ctx, CreateMessage::new().embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+fn parse_spam(value: &str) -> Option<(usize, i64)> {
+    let (count, seconds) = value.split_once('/')?;
+    let count = count.trim().parse::<usize>().ok().filter(|c| *c > 1)?;
+    let seconds = seconds.trim().parse::<i64>().ok().filter(|s| *s > 0)?;
+
+    Some((count, seconds))
+}
@@ -1,16 +1,43 @@
+use std::collections::HashMap;
+
 use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use regex::Regex;
 use serenity::{
-    all::{CommandDataOptionValue, CommandInteraction, CommandOptionType, Reaction, ReactionType},
+    all::{
+        ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType, Mentionable,
+        Message, Reaction, ReactionType, RoleId, UserId,
+    },
     builder::{
-        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
-        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage,
     },
     client::Context,
     model::Permissions,
 };
 use sqlx::SqlitePool;
 
-use crate::database;
+use crate::{
+    database,
+    settings::{self, Setting},
+    GUILD_ID,
+};
+
+use super::{
+    messagelog::truncate,
+    warn::{self, Severity},
+};
+
+const CHANNEL_KIND: &str = "channel";
+const ROLE_KIND: &str = "role";
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum BlacklistAction {
+    Delete,
+    Flag,
+}
 
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
@@ -25,6 +52,15 @@ pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
                 .add_sub_option(
                     CreateCommandOption::new(CommandOptionType::String, "emoji", "Emoji")
                         .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "action",
+                        "Action for messages containing the emoji (delete)",
+                    )
+                    .add_string_choice("Delete", BlacklistAction::Delete.to_string())
+                    .add_string_choice("Flag", BlacklistAction::Flag.to_string()),
                 ),
             )
             .add_option(
@@ -38,11 +74,26 @@ pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
                         .required(true),
                 ),
             )
+            .add_option(scope_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "exempt",
+                "Allow a blacklisted emoji in a channel or for a role",
+            )))
+            .add_option(scope_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "unexempt",
+                "Remove an exemption of a blacklisted emoji",
+            )))
             .add_option(CreateCommandOption::new(
                 CommandOptionType::SubCommand,
                 "list",
                 "List blacklist",
             ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "offenders",
+                "List users who used blacklisted emojis",
+            ))
             .default_member_permissions(Permissions::ADMINISTRATOR)
             .dm_permission(false),
     );
@@ -52,25 +103,276 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
     match command.data.options[0].name.as_str() {
         "add" => add(ctx, command, pool).await,
         "remove" => remove(ctx, command, pool).await,
+        "exempt" => exempt(ctx, command, true, pool).await,
+        "unexempt" => exempt(ctx, command, false, pool).await,
         "list" => list(ctx, command, pool).await,
+        "offenders" => offenders(ctx, command, pool).await,
         _ => Err(anyhow!("Not a subcommand")),
     }
 }
 
+fn scope_options(subcommand: CreateCommandOption) -> CreateCommandOption {
+    subcommand
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::String, "emoji", "Emoji").required(true),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "Channel",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Role,
+            "role",
+            "Role",
+        ))
+}
+
 pub async fn reaction_add(ctx: &Context, reaction: &Reaction, pool: &SqlitePool) -> Result<()> {
-    if let ReactionType::Unicode(emoji) = &reaction.emoji {
-        if database::get_blacklist(pool)
+    let keys = match &reaction.emoji {
+        ReactionType::Unicode(emoji) => vec![emoji.clone()],
+        ReactionType::Custom { id, name, .. } => {
+            let mut keys = vec![id.to_string()];
+            keys.extend(name.clone());
+            keys
+        }
+        _ => return Ok(()),
+    };
+
+    let roles = reaction
+        .member
+        .as_ref()
+        .map(|m| m.roles.clone())
+        .unwrap_or_default();
+
+    let Some(blacklist) = blacklisted(&keys, reaction.channel_id, &roles, pool).await? else {
+        return Ok(());
+    };
+
+    reaction.delete(&ctx).await?;
+
+    if let Some(user) = reaction.user_id {
+        offense(ctx, user, &blacklist.emoji, reaction.channel_id, pool).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn message(ctx: &Context, message: &Message, pool: &SqlitePool) -> Result<()> {
+    if message.author.bot {
+        return Ok(());
+    }
+
+    let keys = emoji_keys(&message.content);
+
+    if keys.is_empty() {
+        return Ok(());
+    }
+
+    let roles = message
+        .member
+        .as_ref()
+        .map(|m| m.roles.clone())
+        .unwrap_or_default();
+
+    let Some(blacklist) = blacklisted(&keys, message.channel_id, &roles, pool).await? else {
+        return Ok(());
+    };
+
+    let action = blacklist
+        .action
+        .parse::<BlacklistAction>()
+        .unwrap_or(BlacklistAction::Delete);
+
+    let mut embed = CreateEmbed::new()
+        .color(0xffa500)
+        .field("Emoji", &blacklist.emoji, true)
+        .field("Content", truncate(&message.content), false)
+        .footer(CreateEmbedFooter::new(format!(
+            "User {}",
+            message.author.id
+        )));
+
+    match action {
+        BlacklistAction::Delete => {
+            // Another listener might have removed the message already
+            let _ = message.delete(&ctx).await;
+
+            embed = embed
+                .title("Blacklisted emoji deleted")
+                .description(format!(
+                    "{} in {}",
+                    message.author.mention(),
+                    message.channel_id.mention()
+                ));
+        }
+        BlacklistAction::Flag => {
+            embed = embed
+                .title("Blacklisted emoji flagged")
+                .description(format!(
+                    "{} in {} - {}",
+                    message.author.mention(),
+                    message.channel_id.mention(),
+                    message.id.link(message.channel_id, Some(GUILD_ID))
+                ));
+        }
+    }
+
+    ChannelId::new(1209471689264603167)
+        .send_message(&ctx, CreateMessage::new().embed(embed))
+        .await?;
+
+    if let BlacklistAction::Delete = action {
+        offense(
+            ctx,
+            message.author.id,
+            &blacklist.emoji,
+            message.channel_id,
+            pool,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Collects every way a blacklist entry could refer to the emojis in the content
+fn emoji_keys(content: &str) -> Vec<String> {
+    let mut keys = Vec::new();
+
+    let re = Regex::new(r"<a?:(\w+):(\d+)>").unwrap();
+    for captures in re.captures_iter(content) {
+        keys.push(captures[2].to_string());
+        keys.push(captures[1].to_string());
+    }
+
+    // Emojis can be sequences joined by zero width joiners, variation selectors and modifiers
+    let mut sequence = String::new();
+    for c in re.replace_all(content, " ").chars() {
+        if is_emoji(c)
+            || (!sequence.is_empty()
+                && (c == '\u{200d}' || c == '\u{fe0f}' || unic_emoji_char::is_emoji_modifier(c)))
+        {
+            sequence.push(c);
+            continue;
+        }
+
+        push_sequence(&mut keys, &mut sequence);
+    }
+    push_sequence(&mut keys, &mut sequence);
+
+    keys
+}
+
+fn is_emoji(c: char) -> bool {
+    // Digits, # and * count as emojis on their own
+    !c.is_ascii() && unic_emoji_char::is_emoji(c)
+}
+
+fn push_sequence(keys: &mut Vec<String>, sequence: &mut String) {
+    if sequence.is_empty() {
+        return;
+    }
+
+    keys.push(sequence.clone());
+    keys.push(sequence.replace('\u{fe0f}', ""));
+    keys.extend(sequence.chars().filter(|c| is_emoji(*c)).map(String::from));
+
+    sequence.clear();
+}
+
+/// Finds the blacklist entry matching any of the keys, unless the channel or one of the roles is exempt
+pub async fn blacklisted(
+    keys: &[String],
+    channel: ChannelId,
+    roles: &[RoleId],
+    pool: &SqlitePool,
+) -> Result<Option<database::DbBlacklist>> {
+    // An exemption for one emoji must not let other blacklisted emojis through
+    for blacklist in database::get_blacklist(pool)
+        .await?
+        .into_iter()
+        .filter(|b| keys.contains(&b.emoji))
+    {
+        let exempt = database::get_blacklist_exemptions_by_emoji(&blacklist.emoji, pool)
             .await?
             .iter()
-            .any(|b| &b.emoji == emoji)
-        {
-            reaction.delete(&ctx).await?;
+            .any(|e| match e.kind.as_str() {
+                CHANNEL_KIND => e.target == channel.get() as i64,
+                ROLE_KIND => roles.iter().any(|r| e.target == r.get() as i64),
+                _ => false,
+            });
+
+        if !exempt {
+            return Ok(Some(blacklist));
         }
     }
 
+    Ok(None)
+}
+
+pub async fn offense(
+    ctx: &Context,
+    user: UserId,
+    emoji: &str,
+    channel: ChannelId,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let now = Utc::now();
+
+    let offense = database::DbBlacklistOffense {
+        user: user.get() as i64,
+        emoji: emoji.to_string(),
+        channel: channel.get() as i64,
+        timestamp: now.naive_utc(),
+    };
+    database::set_blacklist_offense(&offense, pool).await?;
+
+    let threshold: i64 = settings::get(Setting::BlacklistWarnThreshold, pool).await?;
+
+    if threshold <= 0 {
+        return Ok(());
+    }
+
+    let days: i64 = settings::get(Setting::BlacklistWarnDays, pool).await?;
+    let count = database::get_blacklist_offense_count_by_user_since(
+        user.get() as i64,
+        (now - Duration::days(days)).naive_utc(),
+        pool,
+    )
+    .await?;
+
+    if count % threshold == 0 {
+        let bot = ctx.http.get_current_user().await?;
+
+        warn::warn(
+            ctx,
+            user,
+            &format!("Used blacklisted emojis {count} times in {days} days"),
+            Severity::Minor,
+            None,
+            &bot,
+            pool,
+        )
+        .await?;
+    }
+
     Ok(())
 }
 
+fn normalize(emoji: &str) -> String {
+    let emoji = emoji.trim();
+
+    // Custom emojis are stored by id, e.g. <:name:123> or <a:name:123>
+    if emoji.starts_with('<') && emoji.ends_with('>') {
+        if let Some(id) = emoji.trim_end_matches('>').rsplit(':').next() {
+            return id.to_string();
+        }
+    }
+
+    emoji.trim_matches(':').to_string()
+}
+
 async fn add(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
     command
         .create_response(
@@ -85,14 +387,22 @@ async fn add(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) ->
         return Err(anyhow!("Not a subcommand"));
     };
 
-    let emoji = options[0].value.as_str().unwrap().trim();
+    let emoji = normalize(options[0].value.as_str().unwrap());
 
     if emoji.is_empty() {
         return Err(anyhow!("Emoji cannot be empty"));
     }
 
+    let action = options
+        .iter()
+        .find(|o| o.name == "action")
+        .and_then(|o| o.value.as_str())
+        .and_then(|a| a.parse::<BlacklistAction>().ok())
+        .unwrap_or(BlacklistAction::Delete);
+
     let blacklist = database::DbBlacklist {
-        emoji: emoji.to_string(),
+        emoji: emoji.clone(),
+        action: action.to_string(),
     };
     database::set_emoji(blacklist, pool).await?;
 
@@ -100,7 +410,7 @@ async fn add(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) ->
         .create_followup(
             &ctx,
             CreateInteractionResponseFollowup::new()
-                .content(format!("Added {emoji} to blacklist"))
+                .content(format!("Added {emoji} to blacklist ({action})"))
                 .ephemeral(true),
         )
         .await?;
@@ -122,15 +432,16 @@ async fn remove(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool)
         return Err(anyhow!("Not a subcommand"));
     };
 
-    let emoji = options[0].value.as_str().unwrap();
+    let emoji = normalize(options[0].value.as_str().unwrap());
 
-    database::delete_emoji_by_emoji(emoji, pool).await?;
+    database::delete_emoji_by_emoji(&emoji, pool).await?;
+    database::delete_blacklist_exemptions_by_emoji(&emoji, pool).await?;
 
     command
         .create_followup(
             &ctx,
             CreateInteractionResponseFollowup::new()
-                .content(format!("Removed {emoji} to blacklist"))
+                .content(format!("Removed {emoji} from blacklist"))
                 .ephemeral(true),
         )
         .await?;
@@ -149,6 +460,7 @@ async fn list(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) ->
         .await?;
 
     let blacklist = database::get_blacklist(pool).await?;
+    let exemptions = database::get_blacklist_exemptions(pool).await?;
 
     command
         .create_followup(
@@ -158,7 +470,27 @@ async fn list(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) ->
                     CreateEmbed::new().title("Blacklist").description(
                         blacklist
                             .iter()
-                            .map(|b| b.emoji.clone())
+                            .map(|b| {
+                                let allowed = exemptions
+                                    .iter()
+                                    .filter(|e| e.emoji == b.emoji)
+                                    .map(|e| match e.kind.as_str() {
+                                        CHANNEL_KIND => format!("<#{}>", e.target),
+                                        _ => format!("<@&{}>", e.target),
+                                    })
+                                    .collect::<Vec<_>>();
+
+                                if allowed.is_empty() {
+                                    format!("{} ({})", b.emoji, b.action)
+                                } else {
+                                    format!(
+                                        "{} ({}) - allowed for {}",
+                                        b.emoji,
+                                        b.action,
+                                        allowed.join(", ")
+                                    )
+                                }
+                            })
                             .collect::<Vec<String>>()
                             .join("\n"),
                     ),
@@ -169,3 +501,112 @@ async fn list(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) ->
 
     Ok(())
 }
+
+async fn exempt(
+    ctx: &Context,
+    command: &CommandInteraction,
+    add: bool,
+    pool: &SqlitePool,
+) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let emoji = normalize(options[0].value.as_str().unwrap());
+
+    let mut exemptions = Vec::new();
+    for option in options.iter().skip(1) {
+        match option.name.as_str() {
+            "channel" => exemptions.push(database::DbBlacklistExemption {
+                emoji: emoji.clone(),
+                kind: CHANNEL_KIND.to_string(),
+                target: option.value.as_channel_id().unwrap().get() as i64,
+            }),
+            "role" => exemptions.push(database::DbBlacklistExemption {
+                emoji: emoji.clone(),
+                kind: ROLE_KIND.to_string(),
+                target: option.value.as_role_id().unwrap().get() as i64,
+            }),
+            _ => {}
+        }
+    }
+
+    if exemptions.is_empty() {
+        return Err(anyhow!("Specify a channel or a role"));
+    }
+
+    for exemption in &exemptions {
+        if add {
+            database::set_blacklist_exemption(exemption, pool).await?;
+        } else {
+            database::delete_blacklist_exemption(exemption, pool).await?;
+        }
+    }
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(if add {
+                    format!("Exempted {emoji}")
+                } else {
+                    format!("Removed exemptions of {emoji}")
+                })
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn offenders(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let mut counts: HashMap<i64, usize> = HashMap::new();
+    for offense in database::get_blacklist_offenses(pool).await? {
+        *counts.entry(offense.user).or_default() += 1;
+    }
+
+    if counts.is_empty() {
+        return Err(anyhow!("Nobody used blacklisted emojis yet"));
+    }
+
+    let mut counts = counts.into_iter().collect::<Vec<_>>();
+    counts.sort_by_key(|(_, c)| std::cmp::Reverse(*c));
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new().title("Blacklist offenders").description(
+                        counts
+                            .iter()
+                            .take(25)
+                            .map(|(user, count)| format!("<@{user}> - {count}"))
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -0,0 +1,496 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use serenity::{
+    all::{
+        ActionRowComponent, ChannelId, CommandInteraction, CommandOptionType, CommandType,
+        CreateActionRow, CreateInputText, CreateModal, InputTextStyle, Mentionable, Message,
+        MessageId, ModalInteraction, Timestamp, User, UserId,
+    },
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, EditMember,
+        EditMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::{database, GUILD_ID};
+
+use super::{
+    appeals::{self, AppealKind},
+    warn::{
+        cached_message, format_minutes, message_to_warned_message,
+        warned_message_to_create_message, WarnedMessage,
+    },
+};
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum CaseKind {
+    Timeout,
+    Kick,
+    Ban,
+}
+
+impl CaseKind {
+    fn verb(&self) -> &'static str {
+        match self {
+            CaseKind::Timeout => "timed out",
+            CaseKind::Kick => "kicked",
+            CaseKind::Ban => "banned",
+        }
+    }
+
+    fn permissions(&self) -> Permissions {
+        match self {
+            CaseKind::Timeout => Permissions::MODERATE_MEMBERS,
+            CaseKind::Kick => Permissions::KICK_MEMBERS,
+            CaseKind::Ban => Permissions::BAN_MEMBERS,
+        }
+    }
+}
+
+pub fn register(name: &str, kind: CaseKind, commands: &mut Vec<CreateCommand>) {
+    let mut command = CreateCommand::new(name)
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::User, "user", "User").required(true),
+        )
+        .add_option(
+            CreateCommandOption::new(CommandOptionType::String, "reason", "Reason").required(true),
+        );
+
+    if let CaseKind::Timeout = kind {
+        command = command.add_option(
+            CreateCommandOption::new(
+                CommandOptionType::Integer,
+                "duration",
+                "Duration in minutes",
+            )
+            .required(true)
+            .min_int_value(1)
+            .max_int_value(28 * 24 * 60),
+        );
+    }
+
+    commands.push(
+        command
+            .description(format!("{} a user", capitalize(&kind.to_string())))
+            .default_member_permissions(kind.permissions())
+            .dm_permission(false),
+    );
+
+    commands.push(
+        CreateCommand::new(name)
+            .kind(CommandType::Message)
+            .default_member_permissions(kind.permissions())
+            .dm_permission(false),
+    );
+
+    commands.push(
+        CreateCommand::new(name)
+            .kind(CommandType::User)
+            .default_member_permissions(kind.permissions())
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(
+    ctx: &Context,
+    command: &CommandInteraction,
+    kind: CaseKind,
+    pool: &SqlitePool,
+) -> Result<()> {
+    match command.data.kind {
+        CommandType::Message => {
+            let message = command.data.resolved.messages.values().next().unwrap();
+
+            command
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::Modal(modal_components(
+                        &command.data.name,
+                        kind,
+                        message.author.id,
+                        Some((message.id, message.channel_id)),
+                    )),
+                )
+                .await?;
+
+            return Ok(());
+        }
+        CommandType::User => {
+            let user = command.data.resolved.users.values().next().unwrap();
+
+            command
+                .create_response(
+                    ctx,
+                    CreateInteractionResponse::Modal(modal_components(
+                        &command.data.name,
+                        kind,
+                        user.id,
+                        None,
+                    )),
+                )
+                .await?;
+
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    command.defer_ephemeral(&ctx).await?;
+
+    let values = command
+        .data
+        .options
+        .iter()
+        .map(|o| (o.name.clone(), o.value.clone()))
+        .collect::<HashMap<_, _>>();
+
+    let user = values["user"].as_user_id().unwrap();
+    let reason = values["reason"].as_str().unwrap().to_string();
+    let duration = values.get("duration").and_then(|v| v.as_i64());
+
+    let db_case = new_case(kind, user, reason, duration, None, &command.user);
+    execute(ctx, kind, db_case, &command.user, pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content("<#1209471689264603167>")
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn modal(
+    ctx: &Context,
+    interaction: &ModalInteraction,
+    kind: CaseKind,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction.defer_ephemeral(&ctx).await?;
+
+    let inputs = interaction
+        .data
+        .components
+        .iter()
+        .flat_map(|r| &r.components)
+        .filter_map(|c| match c {
+            ActionRowComponent::InputText(i) => Some(i),
+            _ => None,
+        })
+        .map(|i| (i.custom_id.clone(), i.value.clone().unwrap()))
+        .collect::<HashMap<_, _>>();
+
+    let user = UserId::new(inputs["user"].parse()?);
+    let reason = inputs["reason"].clone();
+    let duration = match inputs.get("duration") {
+        Some(duration) => match duration.trim().parse::<i64>() {
+            Ok(minutes) if (1..=28 * 24 * 60).contains(&minutes) => Some(minutes),
+            _ => return Err(anyhow!("Duration has to be between 1 and 40320 minutes")),
+        },
+        None => None,
+    };
+
+    let message = match (inputs.get("message"), inputs.get("channel")) {
+        (Some(message_id), Some(channel_id)) => Some(
+            cached_message(
+                ctx,
+                ChannelId::new(channel_id.parse()?),
+                MessageId::new(message_id.parse()?),
+                pool,
+            )
+            .await?,
+        ),
+        _ => None,
+    };
+
+    let db_case = new_case(
+        kind,
+        user,
+        reason,
+        duration,
+        message.as_ref().map(message_to_warned_message),
+        &interaction.user,
+    );
+    execute(ctx, kind, db_case, &interaction.user, pool).await?;
+
+    if let Some(message) = message {
+        let _ = message.delete(&ctx).await;
+    }
+
+    interaction
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content("<#1209471689264603167>")
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub fn new_case(
+    kind: CaseKind,
+    user: UserId,
+    reason: String,
+    duration: Option<i64>,
+    warned_message: Option<WarnedMessage>,
+    moderator: &User,
+) -> database::DbCase {
+    database::DbCase {
+        kind: kind.to_string(),
+        user: user.get() as i64,
+        moderator: moderator.id.get() as i64,
+        reason,
+        duration,
+        dm: false,
+        message: warned_message.map(|m| serde_json::to_string(&m).unwrap()),
+        timestamp: Utc::now().naive_utc(),
+    }
+}
+
+pub async fn execute(
+    ctx: &Context,
+    kind: CaseKind,
+    mut db_case: database::DbCase,
+    moderator: &User,
+    pool: &SqlitePool,
+) -> Result<()> {
+    if db_case.user == 246684413075652612 {
+        return Err(anyhow!("Nice try nerd"));
+    }
+
+    let user = ctx.http.get_user(UserId::new(db_case.user as u64)).await?;
+    let channel = ChannelId::new(1209471689264603167);
+    let duration = db_case.duration;
+    let reason = db_case.reason.clone();
+
+    let warned_message = db_case
+        .message
+        .as_ref()
+        .and_then(|m| serde_json::from_str::<WarnedMessage>(m).ok());
+
+    let action = match duration {
+        Some(minutes) => format!("{} for {}", kind.verb(), format_minutes(minutes)),
+        None => kind.verb().to_string(),
+    };
+
+    let create_message = match &warned_message {
+        Some(warned_message) => Some(warned_message_to_create_message(ctx, warned_message).await),
+        None => None,
+    };
+
+    let audit_log_reason = format!("{reason} ({})", moderator.name)
+        .chars()
+        .take(512)
+        .collect::<String>();
+
+    let dm = match kind {
+        CaseKind::Timeout => {
+            apply(ctx, kind, user.id, duration, &audit_log_reason).await?;
+
+            send_dm(ctx, &user, &action, &reason, create_message.clone()).await
+        }
+        // Kicked and banned users can't be messaged anymore, so the dm has to go out first
+        CaseKind::Kick | CaseKind::Ban => {
+            let dm = send_dm(ctx, &user, &action, &reason, create_message.clone()).await;
+
+            if let Err(why) = apply(ctx, kind, user.id, duration, &audit_log_reason).await {
+                if let Some(dm) = &dm {
+                    let _ = dm
+                        .channel_id
+                        .send_message(
+                            ctx,
+                            CreateMessage::new().content(format!(
+                                "Please disregard the previous message, you have not been {}.",
+                                kind.verb()
+                            )),
+                        )
+                        .await;
+                }
+
+                return Err(why);
+            }
+
+            dm
+        }
+    };
+    let dmed = dm.is_some();
+
+    db_case.dm = dmed;
+    db_case.timestamp = Utc::now().naive_utc();
+
+    let id = database::set_case(&db_case, pool).await?;
+
+    // Kicks have nothing left to revoke, so they can't be appealed
+    if let (CaseKind::Timeout | CaseKind::Ban, Some(mut dm)) = (kind, dm) {
+        let _ = dm
+            .edit(
+                ctx,
+                EditMessage::new().components(vec![appeals::button(AppealKind::Case, id)]),
+            )
+            .await;
+    }
+
+    let mut embed = CreateEmbed::default()
+        .color(0xff0000)
+        .title(format!("{} {}!", capitalize(kind.verb()), user.name))
+        .field("Case", id.to_string(), true)
+        .field("User", user.mention().to_string(), true)
+        .field("Reason", reason, true);
+
+    if let Some(minutes) = duration {
+        embed = embed.field("Duration", format_minutes(minutes), true);
+    }
+
+    embed = embed
+        .field("Dm", if dmed { "✅" } else { "❌" }, true)
+        .field(
+            "Message",
+            if create_message.is_some() {
+                "✅"
+            } else {
+                "❌"
+            },
+            true,
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "{} by {}",
+            capitalize(kind.verb()),
+            moderator.name
+        )));
+
+    channel
+        .send_message(ctx, CreateMessage::new().embed(embed))
+        .await?;
+
+    if let Some(create_message) = create_message {
+        channel
+            .send_message(ctx, CreateMessage::new().content("Violating message:"))
+            .await?;
+        channel.send_message(ctx, create_message).await?;
+    }
+
+    Ok(())
+}
+
+fn modal_components(
+    name: &str,
+    kind: CaseKind,
+    user: UserId,
+    message: Option<(MessageId, ChannelId)>,
+) -> CreateModal {
+    let mut components = vec![CreateActionRow::InputText(
+        CreateInputText::new(InputTextStyle::Paragraph, "Reason", "reason")
+            .placeholder("Short and precise reason"),
+    )];
+
+    if let CaseKind::Timeout = kind {
+        components.push(CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Short, "Duration in minutes", "duration")
+                .value("60"),
+        ));
+    }
+
+    components.push(CreateActionRow::InputText(
+        CreateInputText::new(InputTextStyle::Short, "User", "user").value(user.to_string()),
+    ));
+
+    if let Some((message_id, channel_id)) = message {
+        components.push(CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Short, "Message", "message")
+                .value(message_id.to_string()),
+        ));
+        components.push(CreateActionRow::InputText(
+            CreateInputText::new(InputTextStyle::Short, "Channel", "channel")
+                .value(channel_id.to_string()),
+        ));
+    }
+
+    CreateModal::new(name, format!("{} a user", capitalize(&kind.to_string())))
+        .components(components)
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+async fn send_dm(
+    ctx: &Context,
+    user: &User,
+    action: &str,
+    reason: &str,
+    create_message: Option<CreateMessage>,
+) -> Option<Message> {
+    let dm_channel = user.create_dm_channel(ctx).await.ok()?;
+
+    let dm = dm_channel
+        .send_message(
+            ctx,
+            CreateMessage::new().content(format!("You have been {action} for: {reason}")),
+        )
+        .await
+        .ok()?;
+
+    if let Some(create_message) = create_message {
+        let _ = dm_channel
+            .send_message(ctx, CreateMessage::new().content("Violating message:"))
+            .await;
+        let _ = dm_channel.send_message(ctx, create_message).await;
+    }
+
+    Some(dm)
+}
+
+async fn apply(
+    ctx: &Context,
+    kind: CaseKind,
+    user: UserId,
+    duration: Option<i64>,
+    audit_log_reason: &str,
+) -> Result<()> {
+    match kind {
+        CaseKind::Timeout => {
+            let until = Timestamp::from_unix_timestamp(
+                (Utc::now() + Duration::minutes(duration.unwrap_or(60))).timestamp(),
+            )?;
+
+            GUILD_ID
+                .edit_member(
+                    ctx,
+                    user,
+                    EditMember::new()
+                        .disable_communication_until_datetime(until)
+                        .audit_log_reason(audit_log_reason),
+                )
+                .await?;
+        }
+        CaseKind::Kick => {
+            GUILD_ID
+                .kick_with_reason(ctx, user, audit_log_reason)
+                .await?
+        }
+        CaseKind::Ban => {
+            GUILD_ID
+                .ban_with_reason(ctx, user, 0, audit_log_reason)
+                .await?
+        }
+    }
+
+    Ok(())
+}
@@ -1,23 +1,26 @@
-use std::time::Duration;
-
 use anyhow::{anyhow, Result};
 use serenity::{
-    all::{CommandInteraction, UserId},
+    all::{CommandInteraction, CommandOptionType},
     builder::{
-        CreateCommand, CreateInteractionResponse, CreateInteractionResponseFollowup,
-        CreateInteractionResponseMessage, CreateMessage,
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
     },
     client::Context,
     model::Permissions,
 };
 use sqlx::SqlitePool;
 
-use crate::database;
+use crate::{database, updater};
 
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
         CreateCommand::new(name)
             .description("Disband this match")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::String,
+                "reason",
+                "Reason",
+            ))
             .default_member_permissions(Permissions::ADMINISTRATOR)
             .dm_permission(false),
     );
@@ -41,27 +44,9 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
         ));
     };
 
-    database::delete_match_by_channel(channel, pool).await?;
-
-    let text = "Your Support Contract with your partner has ended. If you would like to re-match with someone, please go back to https://discord.com/channels/1008493665116758167/1144488145228923020 and hit the Match button.";
-
-    if let Ok(channel) = UserId::new(db_match.user1 as u64)
-        .create_dm_channel(&ctx)
-        .await
-    {
-        let _ = channel
-            .send_message(ctx, CreateMessage::new().content(text))
-            .await;
-    }
+    let reason = command.data.options.first().and_then(|o| o.value.as_str());
 
-    if let Ok(channel) = UserId::new(db_match.user2 as u64)
-        .create_dm_channel(&ctx)
-        .await
-    {
-        let _ = channel
-            .send_message(ctx, CreateMessage::new().content(text))
-            .await;
-    }
+    updater::disband(&db_match, Some(command.user.id), reason, &ctx.http, pool).await?;
 
     command
         .create_followup(
@@ -71,16 +56,5 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
         )
         .await?;
 
-    {
-        let ctx = ctx.clone();
-        let channel = command.channel_id;
-
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_secs(5)).await;
-
-            let _ = channel.delete(&ctx).await;
-        });
-    }
-
     Ok(())
 }
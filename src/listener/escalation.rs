@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::database;
+
+use super::warn::format_minutes;
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Warning escalation")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "set",
+                    "Set an escalation step",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "warns", "Warnings")
+                        .required(true)
+                        .min_int_value(1),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "days", "Days")
+                        .required(true)
+                        .min_int_value(1),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "action", "Action")
+                        .required(true)
+                        .add_string_choice("Timeout", "timeout")
+                        .add_string_choice("Ban", "ban"),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "duration",
+                        "Timeout duration in minutes",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(28 * 24 * 60),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "delete",
+                    "Delete an escalation step",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "warns", "Warnings")
+                        .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List escalation steps",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    match command.data.options[0].name.as_str() {
+        "set" => set(ctx, command, pool).await,
+        "delete" => delete(ctx, command, pool).await,
+        "list" => list(ctx, command, pool).await,
+        _ => Err(anyhow!("Not a subcommand")),
+    }
+}
+
+async fn set(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let warns = options[0].value.as_i64().unwrap();
+    let days = options[1].value.as_i64().unwrap();
+    let action = options[2].value.as_str().unwrap().to_string();
+    let duration = options.get(3).and_then(|o| o.value.as_i64());
+
+    let escalation = database::DbEscalation {
+        warns,
+        days,
+        action,
+        duration,
+    };
+    database::set_escalation(&escalation, pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!(
+                    "Set escalation step {}",
+                    escalation_to_string(&escalation)
+                ))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn delete(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let warns = options[0].value.as_i64().unwrap();
+
+    database::delete_escalation_by_warns(warns, pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!("Deleted escalation step for {warns} warnings"))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn list(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let mut escalations = database::get_escalations_order_by_warns_desc(pool).await?;
+    escalations.reverse();
+
+    if escalations.is_empty() {
+        return Err(anyhow!("No escalation steps"));
+    }
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new().title("Escalation").description(
+                        escalations
+                            .iter()
+                            .map(escalation_to_string)
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn escalation_to_string(escalation: &database::DbEscalation) -> String {
+    let action = match escalation.action.as_str() {
+        "timeout" => format!(
+            "Timeout {}",
+            format_minutes(escalation.duration.unwrap_or(60))
+        ),
+        _ => "Ban".to_string(),
+    };
+
+    format!(
+        "{} warnings in {} days - {action}",
+        escalation.warns, escalation.days
+    )
+}
@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, ComponentInteraction, Mentionable},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        EditInteractionResponse,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::{database, updater};
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Support contract history of a user")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::User, "user", "User").required(true),
+            )
+            .default_member_permissions(Permissions::MANAGE_NICKNAMES)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let user_id = command.data.options[0].value.as_user_id().unwrap();
+    let user = user_id.get() as i64;
+
+    let histories = database::get_match_histories_by_user(user, pool).await?;
+
+    if histories.is_empty() {
+        return Err(anyhow!("This user has no match history"));
+    }
+
+    let received = histories
+        .iter()
+        .filter_map(|h| {
+            if h.user1 == user {
+                h.rating2
+            } else {
+                h.rating1
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let average = if received.is_empty() {
+        "-".to_string()
+    } else {
+        format!(
+            "{:.2} ({} ratings)",
+            received.iter().sum::<i64>() as f64 / received.len() as f64,
+            received.len()
+        )
+    };
+
+    let message = histories
+        .iter()
+        .take(20)
+        .map(|h| {
+            let (partner, given, received) = if h.user1 == user {
+                (h.user2, h.rating1, h.rating2)
+            } else {
+                (h.user1, h.rating2, h.rating1)
+            };
+
+            let disbander = h
+                .disbander
+                .map(|d| format!("<@{d}>"))
+                .unwrap_or("automatically".to_string());
+
+            format!(
+                "<t:{}:d> - <t:{}:d> with <@{partner}> ({})\nDisbanded by {disbander}{}\nGiven: {} - Received: {}",
+                h.started.and_utc().timestamp(),
+                h.ended.and_utc().timestamp(),
+                updater::region_name(h.region),
+                h.reason
+                    .as_ref()
+                    .map(|r| format!(": {r}"))
+                    .unwrap_or_default(),
+                given.map(|r| "⭐".repeat(r as usize)).unwrap_or("-".to_string()),
+                received.map(|r| "⭐".repeat(r as usize)).unwrap_or("-".to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title("Match History")
+                        .description(format!(
+                            "{}\nMatches: {}\nAverage rating: {average}\n\n{}",
+                            user_id.mention(),
+                            histories.len(),
+                            message.join("\n\n")
+                        )),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let id: i64 = parts.next().ok_or_else(|| anyhow!("No id"))?.parse()?;
+    let rating: i64 = parts.next().ok_or_else(|| anyhow!("No rating"))?.parse()?;
+
+    if !(1..=5).contains(&rating) {
+        return Err(anyhow!("Ratings have to be between 1 and 5"));
+    }
+
+    let user = interaction.user.id.get() as i64;
+
+    let history = database::get_match_history_by_id(id, pool).await?;
+
+    if history.user1 != user && history.user2 != user {
+        return Err(anyhow!("You were not part of this match"));
+    }
+
+    database::set_match_history_rating(id, user, rating, pool).await?;
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .content(format!(
+                    "{}\n\nThank you! You rated your partnership {}",
+                    interaction.message.content,
+                    "⭐".repeat(rating as usize)
+                ))
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
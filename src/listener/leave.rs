@@ -0,0 +1,184 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serenity::{
+    all::{ButtonStyle, CommandInteraction, ComponentInteraction, UserId},
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        EditInteractionResponse,
+    },
+    client::Context,
+};
+use sqlx::SqlitePool;
+
+use crate::{database, updater};
+
+const CONFIRM_ID: &str = "confirm";
+const REQUEUE_ID: &str = "requeue";
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Leave your support contract")
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let channel = command.channel_id.get() as i64;
+    let user = command.user.id.get() as i64;
+
+    participant_match(channel, user, pool).await?;
+
+    command.create_followup(&ctx, confirmation()).await?;
+
+    Ok(())
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    match interaction.data.custom_id.split(':').nth(1) {
+        Some(CONFIRM_ID) => leave(ctx, interaction, false, pool).await,
+        Some(REQUEUE_ID) => leave(ctx, interaction, true, pool).await,
+        _ => {
+            interaction
+                .create_response(
+                    &ctx,
+                    CreateInteractionResponse::Defer(
+                        CreateInteractionResponseMessage::new().ephemeral(true),
+                    ),
+                )
+                .await?;
+
+            let channel = interaction.channel_id.get() as i64;
+            let user = interaction.user.id.get() as i64;
+
+            participant_match(channel, user, pool).await?;
+
+            interaction.create_followup(&ctx, confirmation()).await?;
+
+            Ok(())
+        }
+    }
+}
+
+async fn leave(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    requeue: bool,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let channel = interaction.channel_id.get() as i64;
+    let user = interaction.user.id.get() as i64;
+
+    let db_match = participant_match(channel, user, pool).await?;
+    let partner = if db_match.user1 == user {
+        db_match.user2
+    } else {
+        db_match.user1
+    };
+
+    updater::disband(
+        &db_match,
+        Some(interaction.user.id),
+        Some("Left by participant"),
+        &ctx.http,
+        pool,
+    )
+    .await?;
+
+    if let Ok(channel) = UserId::new(partner as u64).create_dm_channel(&ctx).await {
+        let _ = channel
+            .send_message(
+                &ctx,
+                CreateMessage::new().content(format!(
+                    "Your partner <@{user}> has left your Support Contract."
+                )),
+            )
+            .await;
+    }
+
+    if requeue {
+        let candidate = database::DbCandidate {
+            user,
+            timestamp: Utc::now().naive_utc(),
+        };
+        database::set_candidate(candidate, pool).await?;
+    }
+
+    updater::log(
+        &format!(
+            "<@{user}> left their match with <@{partner}>{}",
+            if requeue { " and re-applied" } else { "" }
+        ),
+        &ctx.http,
+    )
+    .await;
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .content(if requeue {
+                    "Left match. You will be notified, once we have found a new partner for you :D"
+                } else {
+                    "Left match. Channel will be deleted in 5s"
+                })
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn participant_match(
+    channel: i64,
+    user: i64,
+    pool: &SqlitePool,
+) -> Result<database::DbMatch> {
+    let Ok(db_match) = database::get_match_by_channel(channel, pool).await else {
+        return Err(anyhow!(
+            "This command has to be executed in a match channel"
+        ));
+    };
+
+    if db_match.user1 != user && db_match.user2 != user {
+        return Err(anyhow!("You are not part of this match"));
+    }
+
+    Ok(db_match)
+}
+
+fn confirmation() -> CreateInteractionResponseFollowup {
+    let name = super::ListenerName::Leave.to_string();
+
+    CreateInteractionResponseFollowup::new()
+        .content(
+            "Are you sure you want to end your Support Contract? Your partner will be notified.",
+        )
+        .components(vec![CreateActionRow::Buttons(vec![
+            CreateButton::new(format!("{name}:{CONFIRM_ID}"))
+                .label("Leave")
+                .style(ButtonStyle::Danger),
+            CreateButton::new(format!("{name}:{REQUEUE_ID}"))
+                .label("Leave and re-apply")
+                .style(ButtonStyle::Primary),
+        ])])
+        .ephemeral(true)
+}
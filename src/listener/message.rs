@@ -1,16 +1,46 @@
+use std::collections::HashMap;
+
 use anyhow::{anyhow, Result};
 use serenity::{
-    all::{ButtonStyle, CommandInteraction, CommandOptionType},
+    all::{
+        ActionRowComponent, ButtonStyle, ChannelId, CommandDataOptionValue, CommandInteraction,
+        CommandOptionType, ComponentInteraction, InputTextStyle, MessageId, ModalInteraction,
+    },
     builder::{
         CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
-        CreateInteractionResponse, CreateInteractionResponseFollowup,
-        CreateInteractionResponseMessage, CreateMessage,
+        CreateEmbedFooter, CreateInputText, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        CreateModal, EditInteractionResponse, EditMessage,
     },
     client::Context,
     model::Permissions,
 };
 use sqlx::SqlitePool;
 
+const CONFIRM_ID: &str = "confirm";
+const CANCEL_ID: &str = "cancel";
+/// Modal inputs can't be longer, even though embed descriptions can
+const DESCRIPTION_LENGTH: u16 = 4000;
+
+#[derive(Clone, Copy, PartialEq, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase")]
+enum Buttons {
+    None,
+    Keep,
+    Register,
+    Match,
+}
+
+impl Buttons {
+    fn components(&self) -> Vec<CreateActionRow> {
+        match self {
+            Buttons::None | Buttons::Keep => Vec::new(),
+            Buttons::Register => vec![super::register::button()],
+            Buttons::Match => vec![match_buttons()],
+        }
+    }
+}
+
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
         CreateCommand::new(name)
@@ -25,6 +55,36 @@ pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
                 "match",
                 "Match message",
             ))
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "embed",
+                    "Compose and post an embed",
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "Channel (this channel)",
+                ))
+                .add_sub_option(buttons_option(false)),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "edit",
+                    "Edit an embed posted by the bot",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "message", "Message id")
+                        .required(true),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Channel,
+                    "channel",
+                    "Channel (this channel)",
+                ))
+                .add_sub_option(buttons_option(true)),
+            )
             .default_member_permissions(Permissions::ADMINISTRATOR)
             .dm_permission(false),
     )
@@ -34,10 +94,32 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
     match command.data.options[0].name.as_str() {
         "verify" => verify(ctx, command, pool).await,
         "match" => r#match(ctx, command, pool).await,
+        "embed" | "edit" => compose(ctx, command).await,
         _ => Err(anyhow!("Not a subcommand")),
     }
 }
 
+fn buttons_option(edit: bool) -> CreateCommandOption {
+    let mut option = CreateCommandOption::new(
+        CommandOptionType::String,
+        "buttons",
+        if edit {
+            "Buttons (keep)"
+        } else {
+            "Buttons (none)"
+        },
+    )
+    .add_string_choice("None", Buttons::None.to_string());
+
+    if edit {
+        option = option.add_string_choice("Keep", Buttons::Keep.to_string());
+    }
+
+    option
+        .add_string_choice("Register", Buttons::Register.to_string())
+        .add_string_choice("Match", Buttons::Match.to_string())
+}
+
 async fn verify(ctx: &Context, command: &CommandInteraction, _: &SqlitePool) -> Result<()> {
     command
         .create_response(
@@ -58,11 +140,7 @@ async fn verify(ctx: &Context, command: &CommandInteraction, _: &SqlitePool) ->
             &ctx,
             CreateMessage::new()
                 .embed(embed)
-                .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
-                    super::ListenerName::Register.to_string(),
-                )
-                .label("Register")
-                .style(ButtonStyle::Primary)])]),
+                .components(vec![super::register::button()]),
         )
         .await?;
 
@@ -98,14 +176,7 @@ async fn r#match(ctx: &Context, command: &CommandInteraction, _: &SqlitePool) ->
             &ctx,
             CreateMessage::new()
                 .embed(embed)
-                .components(vec![CreateActionRow::Buttons(vec![
-                    CreateButton::new(super::ListenerName::Apply.to_string())
-                        .label("Apply")
-                        .style(ButtonStyle::Primary),
-                    CreateButton::new(super::ListenerName::Unapply.to_string())
-                        .label("Unapply")
-                        .style(ButtonStyle::Danger),
-                ])]),
+                .components(vec![match_buttons()]),
         )
         .await?;
 
@@ -120,3 +191,357 @@ async fn r#match(ctx: &Context, command: &CommandInteraction, _: &SqlitePool) ->
 
     Ok(())
 }
+
+fn match_buttons() -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(super::ListenerName::Apply.to_string())
+            .label("Apply")
+            .style(ButtonStyle::Primary),
+        CreateButton::new(super::ListenerName::Unapply.to_string())
+            .label("Unapply")
+            .style(ButtonStyle::Danger),
+        CreateButton::new(format!(
+            "{}:{}",
+            super::ListenerName::Apply,
+            super::apply::STATUS_ID
+        ))
+        .label("Status")
+        .style(ButtonStyle::Secondary),
+    ])
+}
+
+async fn compose(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let values = options
+        .iter()
+        .map(|o| (o.name.as_str(), &o.value))
+        .collect::<HashMap<_, _>>();
+
+    let channel = values
+        .get("channel")
+        .and_then(|v| v.as_channel_id())
+        .unwrap_or(command.channel_id);
+
+    let editing = command.data.options[0].name == "edit";
+
+    let buttons = values
+        .get("buttons")
+        .and_then(|v| v.as_str())
+        .and_then(|b| b.parse().ok())
+        .unwrap_or(if editing {
+            Buttons::Keep
+        } else {
+            Buttons::None
+        });
+
+    let mut embed = None;
+    let mut message_id = 0;
+
+    if editing {
+        // The modal response has to come first, so errors are reported directly
+        let message = match values
+            .get("message")
+            .and_then(|v| v.as_str())
+            .and_then(|id| id.trim().parse::<u64>().ok())
+            .filter(|id| *id != 0)
+        {
+            Some(id) => channel.message(ctx, MessageId::new(id)).await.ok(),
+            None => None,
+        };
+
+        let Some(message) = message else {
+            return respond(ctx, command, "Couldn't find that message in the channel").await;
+        };
+
+        if message.author.id != ctx.cache.current_user().id {
+            return respond(
+                ctx,
+                command,
+                "Only messages posted by the bot can be edited",
+            )
+            .await;
+        }
+
+        message_id = message.id.get();
+        embed = message.embeds.into_iter().next();
+
+        if embed
+            .as_ref()
+            .and_then(|e| e.description.as_ref())
+            .is_some_and(|d| d.chars().count() > DESCRIPTION_LENGTH as usize)
+        {
+            return respond(
+                ctx,
+                command,
+                "The description of this embed is too long to edit here",
+            )
+            .await;
+        }
+    }
+
+    let title = embed.as_ref().and_then(|e| e.title.clone());
+    let description = embed.as_ref().and_then(|e| e.description.clone());
+    let color = embed
+        .as_ref()
+        .and_then(|e| e.colour)
+        .map(|c| format!("#{:06x}", c.0));
+    let image = embed
+        .as_ref()
+        .and_then(|e| e.image.as_ref())
+        .map(|i| i.url.clone());
+    let footer = embed
+        .as_ref()
+        .and_then(|e| e.footer.as_ref())
+        .map(|f| f.text.clone());
+
+    let input = |style, label: &str, id: &str, max: u16, value: Option<String>| {
+        let mut input = CreateInputText::new(style, label, id)
+            .required(false)
+            .max_length(max);
+
+        if let Some(value) = value {
+            input = input.value(value);
+        }
+
+        CreateActionRow::InputText(input)
+    };
+
+    let custom_id = format!(
+        "{}:{channel}:{message_id}:{buttons}",
+        super::ListenerName::Message
+    );
+
+    command
+        .create_response(
+            ctx,
+            CreateInteractionResponse::Modal(
+                CreateModal::new(custom_id, if editing { "Edit embed" } else { "Embed" })
+                    .components(vec![
+                        input(InputTextStyle::Short, "Title", "title", 256, title),
+                        input(
+                            InputTextStyle::Paragraph,
+                            "Description",
+                            "description",
+                            DESCRIPTION_LENGTH,
+                            description,
+                        ),
+                        input(InputTextStyle::Short, "Color (#ffa500)", "color", 7, color),
+                        input(InputTextStyle::Short, "Image url", "image", 2048, image),
+                        input(InputTextStyle::Short, "Footer", "footer", 2048, footer),
+                    ]),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn modal(ctx: &Context, interaction: &ModalInteraction, _: &SqlitePool) -> Result<()> {
+    interaction.defer_ephemeral(&ctx).await?;
+
+    let inputs = interaction
+        .data
+        .components
+        .iter()
+        .flat_map(|r| &r.components)
+        .filter_map(|c| match c {
+            ActionRowComponent::InputText(i) => Some(i),
+            _ => None,
+        })
+        .filter_map(|i| {
+            i.value
+                .as_ref()
+                .filter(|v| !v.trim().is_empty())
+                .map(|v| (i.custom_id.as_str(), v.trim().to_string()))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let target = interaction
+        .data
+        .custom_id
+        .split_once(':')
+        .map(|(_, target)| target)
+        .ok_or_else(|| anyhow!("No target"))?;
+
+    let mut parts = target.split(':');
+    let channel = ChannelId::new(parts.next().ok_or_else(|| anyhow!("No channel"))?.parse()?);
+    let message: u64 = parts.next().ok_or_else(|| anyhow!("No message"))?.parse()?;
+    let buttons: Buttons = parts.next().ok_or_else(|| anyhow!("No buttons"))?.parse()?;
+
+    // Edits start from the posted embed, so thumbnails, fields and authors are kept
+    let mut original = if message == 0 {
+        None
+    } else {
+        channel
+            .message(ctx, MessageId::new(message))
+            .await?
+            .embeds
+            .into_iter()
+            .next()
+    };
+
+    if let Some(original) = &mut original {
+        original.title = None;
+        original.description = None;
+        original.colour = None;
+        original.image = None;
+
+        if original.footer.as_ref().map(|f| &f.text) != inputs.get("footer") {
+            original.footer = None;
+        }
+    }
+
+    if !["title", "description", "image"]
+        .iter()
+        .any(|i| inputs.contains_key(i))
+        && original
+            .as_ref()
+            .is_none_or(|e| e.fields.is_empty() && e.thumbnail.is_none() && e.author.is_none())
+    {
+        return Err(anyhow!("The embed needs a title, description or image"));
+    }
+
+    let mut embed = original.map(CreateEmbed::from).unwrap_or_default();
+
+    if let Some(title) = inputs.get("title") {
+        embed = embed.title(title);
+    }
+    if let Some(description) = inputs.get("description") {
+        embed = embed.description(description);
+    }
+    if let Some(color) = inputs.get("color") {
+        let color = u32::from_str_radix(color.trim_start_matches('#'), 16)
+            .map_err(|_| anyhow!("Invalid color {color}, use a hex color like #ffa500"))?;
+
+        embed = embed.color(color);
+    }
+    if let Some(image) = inputs.get("image") {
+        if !image.starts_with("https://") && !image.starts_with("http://") {
+            return Err(anyhow!("Invalid image url {image}"));
+        }
+
+        embed = embed.image(image);
+    }
+    if let Some(footer) = inputs.get("footer") {
+        embed = embed.footer(CreateEmbedFooter::new(footer));
+    }
+
+    let mut components = buttons.components();
+    components.push(CreateActionRow::Buttons(vec![
+        CreateButton::new(format!(
+            "{}:{CONFIRM_ID}:{target}",
+            super::ListenerName::Message
+        ))
+        .label(if message == 0 { "Post" } else { "Save" })
+        .style(ButtonStyle::Success),
+        CreateButton::new(format!("{}:{CANCEL_ID}", super::ListenerName::Message))
+            .label("Cancel")
+            .style(ButtonStyle::Secondary),
+    ]));
+
+    interaction
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content("Preview")
+                .embed(embed)
+                .components(components)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    _: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+
+    if parts.next() != Some(CONFIRM_ID) {
+        interaction
+            .edit_response(
+                &ctx,
+                EditInteractionResponse::new()
+                    .content("Cancelled")
+                    .embeds(Vec::new())
+                    .components(Vec::new()),
+            )
+            .await?;
+
+        return Ok(());
+    }
+
+    let channel = ChannelId::new(parts.next().ok_or_else(|| anyhow!("No channel"))?.parse()?);
+    let message: u64 = parts.next().ok_or_else(|| anyhow!("No message"))?.parse()?;
+    let buttons: Buttons = parts.next().ok_or_else(|| anyhow!("No buttons"))?.parse()?;
+
+    // The preview carries the composed embed
+    let embed = interaction
+        .message
+        .embeds
+        .first()
+        .cloned()
+        .map(CreateEmbed::from)
+        .ok_or_else(|| anyhow!("No embed"))?;
+
+    let posted = if message == 0 {
+        channel
+            .send_message(
+                ctx,
+                CreateMessage::new()
+                    .embed(embed)
+                    .components(buttons.components()),
+            )
+            .await?
+    } else {
+        let mut edit_message = EditMessage::new().embed(embed);
+
+        if buttons != Buttons::Keep {
+            edit_message = edit_message.components(buttons.components());
+        }
+
+        channel
+            .edit_message(ctx, MessageId::new(message), edit_message)
+            .await?
+    };
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .content(format!(
+                    "{} {}",
+                    if message == 0 { "Posted" } else { "Saved" },
+                    posted.link()
+                ))
+                .components(Vec::new()),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn respond(ctx: &Context, command: &CommandInteraction, content: &str) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Message(
+                CreateInteractionResponseMessage::new()
+                    .content(content)
+                    .ephemeral(true),
+            ),
+        )
+        .await?;
+
+    Ok(())
+}
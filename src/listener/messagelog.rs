@@ -0,0 +1,265 @@
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{
+        ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType, Mentionable,
+        Message, MessageId, MessageUpdateEvent,
+    },
+    builder::{
+        CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+    GUILD_ID,
+};
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Message edit and delete logging")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "exclude",
+                    "Stop logging a channel",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Channel")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "include",
+                    "Log a previously excluded channel again",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Channel, "channel", "Channel")
+                        .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List excluded channels",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let content = match command.data.options[0].name.as_str() {
+        "exclude" => {
+            let channel = options[0].value.as_channel_id().unwrap();
+
+            let exclusion = database::DbLogExclusion {
+                channel: channel.get() as i64,
+            };
+            database::set_log_exclusion(&exclusion, pool).await?;
+
+            format!("Excluded {} from message logging", channel.mention())
+        }
+        "include" => {
+            let channel = options[0].value.as_channel_id().unwrap();
+
+            database::delete_log_exclusion_by_channel(channel.get() as i64, pool).await?;
+
+            format!("Included {} in message logging", channel.mention())
+        }
+        "list" => {
+            let exclusions = database::get_log_exclusions(pool).await?;
+
+            if exclusions.is_empty() {
+                "No channels are excluded".to_string()
+            } else {
+                exclusions
+                    .iter()
+                    .map(|e| format!("<#{}>", e.channel))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn message_update(
+    ctx: &Context,
+    old: Option<&Message>,
+    event: &MessageUpdateEvent,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let Some(content) = &event.content else {
+        return Ok(());
+    };
+
+    if old.is_some_and(|old| &old.content == content) {
+        return Ok(());
+    }
+
+    let Some(author) = event.author.as_ref().or(old.map(|m| &m.author)) else {
+        return Ok(());
+    };
+
+    if author.bot {
+        return Ok(());
+    }
+
+    let Some(log_channel) = log_channel(event.channel_id, pool).await? else {
+        return Ok(());
+    };
+
+    let embed = CreateEmbed::new()
+        .color(0xffa500)
+        .title("Message edited")
+        .description(format!(
+            "{} in {} - {}",
+            author.mention(),
+            event.channel_id.mention(),
+            event.id.link(event.channel_id, Some(GUILD_ID))
+        ))
+        .field(
+            "Before",
+            truncate(old.map(|m| m.content.as_str()).unwrap_or("*Not cached*")),
+            false,
+        )
+        .field("After", truncate(content), false)
+        .footer(CreateEmbedFooter::new(format!("User {}", author.id)));
+
+    log_channel
+        .send_message(ctx, CreateMessage::new().embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn message_delete(
+    ctx: &Context,
+    channel_id: &ChannelId,
+    message_id: &MessageId,
+    old: Option<&Message>,
+    pool: &SqlitePool,
+) -> Result<()> {
+    if old.is_some_and(|m| m.author.bot) {
+        return Ok(());
+    }
+
+    let Some(log_channel) = log_channel(*channel_id, pool).await? else {
+        return Ok(());
+    };
+
+    let mut embed = CreateEmbed::new().color(0xff0000).title("Message deleted");
+    let mut create_message = CreateMessage::new();
+
+    match old {
+        Some(message) => {
+            embed = embed
+                .description(format!(
+                    "{} in {}",
+                    message.author.mention(),
+                    channel_id.mention()
+                ))
+                .field("Content", truncate(&message.content), false)
+                .footer(CreateEmbedFooter::new(format!(
+                    "User {} - Message {message_id}",
+                    message.author.id
+                )));
+
+            if !message.attachments.is_empty() {
+                embed = embed.field(
+                    "Attachments",
+                    truncate(
+                        &message
+                            .attachments
+                            .iter()
+                            .map(|a| a.url.as_str())
+                            .collect::<Vec<_>>()
+                            .join("\n"),
+                    ),
+                    false,
+                );
+            }
+
+            // The cdn keeps serving attachments for a short while after deletion
+            for attachment in &message.attachments {
+                if let Ok(attachment) = CreateAttachment::url(ctx, &attachment.url).await {
+                    create_message = create_message.add_file(attachment);
+                }
+            }
+        }
+        None => {
+            embed = embed
+                .description(format!("Message {message_id} in {}", channel_id.mention()))
+                .field("Content", "*Not cached*", false);
+        }
+    }
+
+    log_channel
+        .send_message(ctx, create_message.embed(embed))
+        .await?;
+
+    Ok(())
+}
+
+async fn log_channel(channel: ChannelId, pool: &SqlitePool) -> Result<Option<ChannelId>> {
+    let log_channel: u64 = settings::get(Setting::MessageLogChannel, pool).await?;
+
+    if log_channel == 0 || log_channel == channel.get() {
+        return Ok(None);
+    }
+
+    if database::get_log_exclusion_by_channel(channel.get() as i64, pool)
+        .await?
+        .is_some()
+    {
+        return Ok(None);
+    }
+
+    Ok(Some(ChannelId::new(log_channel)))
+}
+
+pub fn truncate(content: &str) -> String {
+    if content.is_empty() {
+        return "*Empty*".to_string();
+    }
+
+    let mut truncated = content.chars().take(1021).collect::<String>();
+    if truncated.len() < content.len() {
+        truncated.push_str("...");
+    }
+
+    truncated
+}
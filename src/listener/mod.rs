@@ -1,85 +1,131 @@
+mod appeals;
 mod apply;
-mod bans;
+mod automod;
 mod blacklist;
 mod blade;
 mod card;
+mod case;
 mod disband;
+mod escalation;
+mod history;
+mod leave;
 mod message;
+mod messagelog;
+mod modstats;
+mod persist;
 mod purge;
+mod raid;
 mod register;
 mod role;
+mod rolemenu;
 mod roles;
 mod rolestats;
+mod settings;
 mod sniff;
 mod sql;
 mod status;
+mod sticky;
 mod uids;
 mod unapply;
 mod unregister;
 mod update;
 mod verify;
 mod warn;
+mod warns;
 
 use anyhow::Result;
 use serenity::{
     all::{
-        CommandInteraction, ComponentInteraction, CreateCommand, GuildId, ModalInteraction,
-        Reaction, User,
+        ChannelId, CommandInteraction, ComponentInteraction, CreateCommand, GuildId,
+        GuildMemberUpdateEvent, Member, Message, MessageId, MessageUpdateEvent, ModalInteraction,
+        Reaction, RoleId, User,
     },
     client::Context,
 };
 use sqlx::SqlitePool;
 
+use case::CaseKind;
+
 #[derive(strum_macros::Display, strum_macros::EnumIter)]
 #[strum(serialize_all = "camelCase")]
 pub enum ListenerName {
+    Appeals,
     Apply,
-    Bans,
+    Automod,
     Blacklist,
     Card,
     Disband,
+    Escalation,
+    History,
+    Leave,
     Message,
+    Messagelog,
+    Modstats,
+    Persist,
+    Raid,
     Register,
     Role,
+    Rolemenu,
     Roles,
     Rolestats,
+    Settings,
     Sniff,
     Sql,
     Status,
+    Sticky,
     Uids,
     Unapply,
     Unregister,
     Update,
     Verify,
     Warn,
+    Warns,
     Purge,
     Blade,
+    Timeout,
+    Kick,
+    Ban,
 }
 
 impl ListenerName {
     pub fn register(&self, commands: &mut Vec<CreateCommand>) {
         match self {
+            ListenerName::Appeals => appeals::register(&self.to_string(), commands),
             ListenerName::Apply => apply::register(&self.to_string(), commands),
-            ListenerName::Bans => bans::register(&self.to_string(), commands),
+            ListenerName::Automod => automod::register(&self.to_string(), commands),
             ListenerName::Blacklist => blacklist::register(&self.to_string(), commands),
             ListenerName::Card => card::register(&self.to_string(), commands),
             ListenerName::Disband => disband::register(&self.to_string(), commands),
+            ListenerName::Escalation => escalation::register(&self.to_string(), commands),
+            ListenerName::History => history::register(&self.to_string(), commands),
+            ListenerName::Leave => leave::register(&self.to_string(), commands),
             ListenerName::Message => message::register(&self.to_string(), commands),
+            ListenerName::Messagelog => messagelog::register(&self.to_string(), commands),
+            ListenerName::Modstats => modstats::register(&self.to_string(), commands),
+            ListenerName::Persist => persist::register(&self.to_string(), commands),
+            ListenerName::Raid => raid::register(&self.to_string(), commands),
             ListenerName::Register => register::register(&self.to_string(), commands),
             ListenerName::Role => role::register(&self.to_string(), commands),
+            ListenerName::Rolemenu => rolemenu::register(&self.to_string(), commands),
             ListenerName::Roles => roles::register(&self.to_string(), commands),
             ListenerName::Rolestats => rolestats::register(&self.to_string(), commands),
+            ListenerName::Settings => settings::register(&self.to_string(), commands),
             ListenerName::Sniff => sniff::register(&self.to_string(), commands),
             ListenerName::Sql => sql::register(&self.to_string(), commands),
             ListenerName::Status => status::register(&self.to_string(), commands),
+            ListenerName::Sticky => sticky::register(&self.to_string(), commands),
             ListenerName::Uids => uids::register(&self.to_string(), commands),
             ListenerName::Unapply => unapply::register(&self.to_string(), commands),
             ListenerName::Unregister => unregister::register(&self.to_string(), commands),
             ListenerName::Update => update::register(&self.to_string(), commands),
             ListenerName::Verify => verify::register(&self.to_string(), commands),
             ListenerName::Warn => warn::register(&self.to_string(), commands),
+            ListenerName::Warns => warns::register(&self.to_string(), commands),
             ListenerName::Purge => purge::register(&self.to_string(), commands),
             ListenerName::Blade => blade::register(&self.to_string(), commands),
+            ListenerName::Timeout => case::register(&self.to_string(), CaseKind::Timeout, commands),
+            ListenerName::Kick => case::register(&self.to_string(), CaseKind::Kick, commands),
+            ListenerName::Ban => case::register(&self.to_string(), CaseKind::Ban, commands),
         }
     }
 
@@ -90,27 +136,42 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
+            ListenerName::Appeals => appeals::command(ctx, command, pool).await,
             ListenerName::Apply => apply::command(ctx, command, pool).await,
-            ListenerName::Bans => bans::command(ctx, command, pool).await,
+            ListenerName::Automod => automod::command(ctx, command, pool).await,
             ListenerName::Blacklist => blacklist::command(ctx, command, pool).await,
             ListenerName::Card => card::command(ctx, command, pool).await,
             ListenerName::Disband => disband::command(ctx, command, pool).await,
+            ListenerName::Escalation => escalation::command(ctx, command, pool).await,
+            ListenerName::History => history::command(ctx, command, pool).await,
+            ListenerName::Leave => leave::command(ctx, command, pool).await,
             ListenerName::Message => message::command(ctx, command, pool).await,
+            ListenerName::Messagelog => messagelog::command(ctx, command, pool).await,
+            ListenerName::Modstats => modstats::command(ctx, command, pool).await,
+            ListenerName::Persist => persist::command(ctx, command, pool).await,
+            ListenerName::Raid => raid::command(ctx, command, pool).await,
             ListenerName::Register => register::command(ctx, command, pool).await,
             ListenerName::Role => role::command(ctx, command, pool).await,
+            ListenerName::Rolemenu => rolemenu::command(ctx, command, pool).await,
             ListenerName::Roles => roles::command(ctx, command, pool).await,
             ListenerName::Rolestats => rolestats::command(ctx, command, pool).await,
+            ListenerName::Settings => settings::command(ctx, command, pool).await,
             ListenerName::Sniff => sniff::command(ctx, command, pool).await,
             ListenerName::Sql => sql::command(ctx, command, pool).await,
             ListenerName::Status => status::command(ctx, command, pool).await,
+            ListenerName::Sticky => sticky::command(ctx, command, pool).await,
             ListenerName::Uids => uids::command(ctx, command, pool).await,
             ListenerName::Unapply => unapply::command(ctx, command, pool).await,
             ListenerName::Unregister => unregister::command(ctx, command, pool).await,
             ListenerName::Update => update::command(ctx, command, pool).await,
             ListenerName::Verify => verify::command(ctx, command, pool).await,
             ListenerName::Warn => warn::command(ctx, command, pool).await,
+            ListenerName::Warns => warns::command(ctx, command, pool).await,
             ListenerName::Purge => purge::command(ctx, command, pool).await,
             ListenerName::Blade => blade::command(ctx, command, pool).await,
+            ListenerName::Timeout => case::command(ctx, command, CaseKind::Timeout, pool).await,
+            ListenerName::Kick => case::command(ctx, command, CaseKind::Kick, pool).await,
+            ListenerName::Ban => case::command(ctx, command, CaseKind::Ban, pool).await,
         }
     }
 
@@ -121,9 +182,16 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
+            ListenerName::Appeals => appeals::component(ctx, interaction, pool).await,
             ListenerName::Apply => apply::component(ctx, interaction, pool).await,
+            ListenerName::History => history::component(ctx, interaction, pool).await,
+            ListenerName::Leave => leave::component(ctx, interaction, pool).await,
+            ListenerName::Message => message::component(ctx, interaction, pool).await,
+            ListenerName::Raid => raid::component(ctx, interaction, pool).await,
             ListenerName::Register => register::component(ctx, interaction, pool).await,
+            ListenerName::Rolemenu => rolemenu::component(ctx, interaction, pool).await,
             ListenerName::Unapply => unapply::component(ctx, interaction, pool).await,
+            ListenerName::Warns => warns::component(ctx, interaction, pool).await,
             _ => Ok(()),
         }
     }
@@ -135,8 +203,13 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
+            ListenerName::Appeals => appeals::modal(ctx, interaction, pool).await,
+            ListenerName::Message => message::modal(ctx, interaction, pool).await,
             ListenerName::Register => register::modal(ctx, interaction, pool).await,
             ListenerName::Warn => warn::modal(ctx, interaction, pool).await,
+            ListenerName::Timeout => case::modal(ctx, interaction, CaseKind::Timeout, pool).await,
+            ListenerName::Kick => case::modal(ctx, interaction, CaseKind::Kick, pool).await,
+            ListenerName::Ban => case::modal(ctx, interaction, CaseKind::Ban, pool).await,
             _ => Ok(()),
         }
     }
@@ -148,6 +221,7 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
+            ListenerName::Settings => settings::autocomplete(ctx, command, pool).await,
             ListenerName::Verify => verify::autocomplete(ctx, command, pool).await,
             _ => Ok(()),
         }
@@ -165,6 +239,58 @@ impl ListenerName {
         }
     }
 
+    pub async fn message(&self, ctx: &Context, message: &Message, pool: &SqlitePool) -> Result<()> {
+        match self {
+            ListenerName::Automod => automod::message(ctx, message, pool).await,
+            ListenerName::Blacklist => blacklist::message(ctx, message, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn member_addition(
+        &self,
+        ctx: &Context,
+        member: &Member,
+        pool: &SqlitePool,
+    ) -> Result<()> {
+        match self {
+            ListenerName::Sticky => sticky::member_addition(ctx, member, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn welcome(&self, ctx: &Context, member: &Member, pool: &SqlitePool) -> Result<()> {
+        match self {
+            ListenerName::Register => register::welcome(ctx, member, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn member_update(
+        &self,
+        ctx: &Context,
+        old_roles: &[RoleId],
+        event: &GuildMemberUpdateEvent,
+        pool: &SqlitePool,
+    ) -> Result<()> {
+        match self {
+            ListenerName::Sticky => sticky::member_update(ctx, old_roles, event, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn hold_member(
+        &self,
+        ctx: &Context,
+        member: &Member,
+        pool: &SqlitePool,
+    ) -> Result<bool> {
+        match self {
+            ListenerName::Raid => raid::hold_member(ctx, member, pool).await,
+            _ => Ok(false),
+        }
+    }
+
     pub async fn ban_add(
         &self,
         ctx: &Context,
@@ -173,7 +299,7 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
-            ListenerName::Bans => bans::ban_add(ctx, guild_id, user, pool).await,
+            ListenerName::Modstats => modstats::ban_add(ctx, guild_id, user, pool).await,
             _ => Ok(()),
         }
     }
@@ -186,7 +312,36 @@ impl ListenerName {
         pool: &SqlitePool,
     ) -> Result<()> {
         match self {
-            ListenerName::Bans => bans::ban_remove(ctx, guild_id, user, pool).await,
+            ListenerName::Modstats => modstats::ban_remove(ctx, guild_id, user, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn message_update(
+        &self,
+        ctx: &Context,
+        old: Option<&Message>,
+        event: &MessageUpdateEvent,
+        pool: &SqlitePool,
+    ) -> Result<()> {
+        match self {
+            ListenerName::Messagelog => messagelog::message_update(ctx, old, event, pool).await,
+            _ => Ok(()),
+        }
+    }
+
+    pub async fn message_delete(
+        &self,
+        ctx: &Context,
+        channel_id: &ChannelId,
+        message_id: &MessageId,
+        old: Option<&Message>,
+        pool: &SqlitePool,
+    ) -> Result<()> {
+        match self {
+            ListenerName::Messagelog => {
+                messagelog::message_delete(ctx, channel_id, message_id, old, pool).await
+            }
             _ => Ok(()),
         }
     }
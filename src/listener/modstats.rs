@@ -0,0 +1,258 @@
+use std::{collections::HashMap, time::Duration as StdDuration};
+
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime, Utc};
+use serenity::{
+    all::{
+        AuditLogEntry, CommandInteraction, CommandOptionType, GuildId, MemberAction, User, UserId,
+    },
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::{guild::audit_log::Action, Permissions},
+};
+use sqlx::SqlitePool;
+
+use crate::{database, updater};
+
+use super::case::CaseKind;
+
+pub const UNBAN: &str = "unban";
+
+#[derive(Default)]
+struct Stats {
+    warns: usize,
+    bans: usize,
+    unbans: usize,
+    timeouts: usize,
+    kicks: usize,
+    purges: usize,
+}
+
+impl Stats {
+    fn total(&self) -> usize {
+        self.warns + self.bans + self.unbans + self.timeouts + self.kicks + self.purges
+    }
+}
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Moderator action statistics")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::Integer, "period", "Period (month)")
+                    .add_int_choice("Day", 1)
+                    .add_int_choice("Week", 7)
+                    .add_int_choice("Month", 30)
+                    .add_int_choice("Year", 365)
+                    .add_int_choice("All time", 0),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::User,
+                "moderator",
+                "Moderator",
+            ))
+            .default_member_permissions(Permissions::BAN_MEMBERS)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let period = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "period")
+        .and_then(|o| o.value.as_i64())
+        .unwrap_or(30);
+
+    let moderator = command
+        .data
+        .options
+        .iter()
+        .find(|o| o.name == "moderator")
+        .and_then(|o| o.value.as_user_id());
+
+    let since = if period > 0 {
+        (Utc::now() - Duration::days(period)).naive_utc()
+    } else {
+        NaiveDateTime::default()
+    };
+
+    let mut stats: HashMap<i64, Stats> = HashMap::new();
+
+    for warn in database::get_warns_since(since, pool).await? {
+        stats.entry(warn.moderator).or_default().warns += 1;
+    }
+
+    for case in database::get_cases_since(since, pool).await? {
+        let entry = stats.entry(case.moderator).or_default();
+
+        if case.kind == CaseKind::Ban.to_string() {
+            entry.bans += 1;
+        } else if case.kind == CaseKind::Timeout.to_string() {
+            entry.timeouts += 1;
+        } else if case.kind == CaseKind::Kick.to_string() {
+            entry.kicks += 1;
+        } else if case.kind == UNBAN {
+            entry.unbans += 1;
+        }
+    }
+
+    for purge in database::get_purges_since(since, pool).await? {
+        stats.entry(purge.moderator).or_default().purges += 1;
+    }
+
+    // Bans from before cases were recorded only have a total without a time
+    if period == 0 {
+        for legacy in database::get_legacy_ban_counts(pool).await? {
+            stats.entry(legacy.moderator).or_default().bans += legacy.count as usize;
+        }
+    }
+
+    let mut stats = stats
+        .into_iter()
+        .filter(|(m, _)| moderator.is_none_or(|moderator| moderator.get() as i64 == *m))
+        .collect::<Vec<_>>();
+    stats.sort_by_key(|(_, s)| std::cmp::Reverse(s.total()));
+
+    let message = stats
+        .iter()
+        .take(25)
+        .map(|(m, s)| {
+            format!(
+                "<@{m}> - {} warns, {} bans, {} unbans, {} timeouts, {} kicks, {} purges",
+                s.warns, s.bans, s.unbans, s.timeouts, s.kicks, s.purges
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let title = match period {
+        1 => "Moderator stats (last day)".to_string(),
+        0 => "Moderator stats (all time)".to_string(),
+        days => format!("Moderator stats (last {days} days)"),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title(title)
+                        .description(if message.is_empty() {
+                            "No moderator actions".to_string()
+                        } else {
+                            message.join("\n")
+                        }),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn ban_add(
+    ctx: &Context,
+    guild_id: &GuildId,
+    user: &User,
+    pool: &SqlitePool,
+) -> Result<()> {
+    record(ctx, guild_id, user, MemberAction::BanAdd, pool).await
+}
+
+pub async fn ban_remove(
+    ctx: &Context,
+    guild_id: &GuildId,
+    user: &User,
+    pool: &SqlitePool,
+) -> Result<()> {
+    record(ctx, guild_id, user, MemberAction::BanRemove, pool).await
+}
+
+async fn record(
+    ctx: &Context,
+    guild_id: &GuildId,
+    user: &User,
+    action: MemberAction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let Some(entry) = audit_log_entry(ctx, guild_id, user.id, action).await else {
+        updater::log(
+            &format!(
+                "Couldn't find the audit log entry for the {} of <@{}>",
+                if let MemberAction::BanAdd = action {
+                    "ban"
+                } else {
+                    "unban"
+                },
+                user.id
+            ),
+            &ctx.http,
+        )
+        .await;
+
+        return Ok(());
+    };
+
+    // Actions done through the bot record their own cases
+    if entry.user_id == ctx.cache.current_user().id {
+        return Ok(());
+    }
+
+    let db_case = database::DbCase {
+        kind: if let MemberAction::BanAdd = action {
+            CaseKind::Ban.to_string()
+        } else {
+            UNBAN.to_string()
+        },
+        user: user.id.get() as i64,
+        moderator: entry.user_id.get() as i64,
+        reason: entry.reason.unwrap_or_default(),
+        duration: None,
+        dm: false,
+        message: None,
+        timestamp: Utc::now().naive_utc(),
+    };
+    database::set_case(&db_case, pool).await?;
+
+    Ok(())
+}
+
+pub async fn audit_log_entry(
+    ctx: &Context,
+    guild_id: &GuildId,
+    user: UserId,
+    action: MemberAction,
+) -> Option<AuditLogEntry> {
+    // The audit log entry isn't always available yet when the event arrives
+    for _ in 0..5 {
+        if let Ok(audit_logs) = guild_id
+            .audit_logs(&ctx, Some(Action::Member(action)), None, None, Some(10))
+            .await
+        {
+            if let Some(entry) = audit_logs.entries.into_iter().find(|e| {
+                e.target_id.map(|id| id.get()) == Some(user.get())
+                    && Utc::now().timestamp() - e.id.created_at().unix_timestamp() < 60
+            }) {
+                return Some(entry);
+            }
+        }
+
+        tokio::time::sleep(StdDuration::from_secs(2)).await;
+    }
+
+    None
+}
@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{CommandDataOptionValue, CommandInteraction, CommandOptionType, Mentionable},
+    builder::{
+        CreateCommand, CreateCommandOption, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::database;
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Roles restored when members rejoin")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "roles",
+                    "Show the saved roles of a user",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "clear",
+                    "Clear the saved roles of a user",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "exclude",
+                    "Never save or restore a role",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "include",
+                    "Save and restore a previously excluded role again",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "exclusions",
+                "List excluded roles",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let content = match command.data.options[0].name.as_str() {
+        "roles" => {
+            let user = options[0].value.as_user_id().unwrap();
+
+            let user_roles = database::get_user_roles_by_user(user.get() as i64, pool).await?;
+
+            match user_roles.first() {
+                Some(user_role) => format!(
+                    "Saved roles of {} (left <t:{}:R>)\n{}",
+                    user.mention(),
+                    user_role.timestamp.and_utc().timestamp(),
+                    user_roles
+                        .iter()
+                        .map(|r| format!("<@&{}>", r.role))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                None => format!("{} has no saved roles", user.mention()),
+            }
+        }
+        "clear" => {
+            let user = options[0].value.as_user_id().unwrap();
+
+            database::delete_user_roles_by_user(user.get() as i64, pool).await?;
+
+            format!("Cleared the saved roles of {}", user.mention())
+        }
+        "exclude" => {
+            let role = options[0].value.as_role_id().unwrap();
+
+            let exclusion = database::DbPersistExclusion {
+                role: role.get() as i64,
+            };
+            database::set_persist_exclusion(&exclusion, pool).await?;
+
+            format!("{} won't be saved or restored anymore", role.mention())
+        }
+        "include" => {
+            let role = options[0].value.as_role_id().unwrap();
+
+            database::delete_persist_exclusion_by_role(role.get() as i64, pool).await?;
+
+            format!("{} will be saved and restored again", role.mention())
+        }
+        "exclusions" => {
+            let exclusions = database::get_persist_exclusions(pool).await?;
+
+            if exclusions.is_empty() {
+                "No roles are excluded".to_string()
+            } else {
+                exclusions
+                    .iter()
+                    .map(|e| format!("<@&{}>", e.role))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -1,35 +1,128 @@
 use anyhow::{anyhow, Result};
+use chrono::{Duration, Utc};
+use regex::Regex;
 use serenity::{
     all::{
-        CommandDataOptionValue, CommandInteraction, CommandOptionType, GetMessages, Mentionable,
+        ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+        CommandOptionType, GetMessages, Mentionable, Message, MessageId, UserId,
     },
     builder::{
-        CreateCommand, CreateCommandOption, CreateInteractionResponse,
-        CreateInteractionResponseFollowup, CreateInteractionResponseMessage,
+        CreateAttachment, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage,
     },
     client::Context,
     model::Permissions,
 };
 use sqlx::SqlitePool;
 
+use crate::{database, updater};
+
+const MAX_PAGES: usize = 50;
+
+#[derive(Default)]
+struct Filter {
+    user: Option<UserId>,
+    contains: Option<String>,
+    regex: Option<Regex>,
+    attachments: bool,
+    links: bool,
+    bots: bool,
+    after: Option<MessageId>,
+    before: Option<MessageId>,
+}
+
+impl Filter {
+    fn from_options(options: &[CommandDataOption]) -> Result<Self> {
+        let mut filter = Filter::default();
+
+        for option in options {
+            match option.name.as_str() {
+                "user" => filter.user = option.value.as_user_id(),
+                "contains" => {
+                    filter.contains = option.value.as_str().map(|s| s.to_lowercase());
+                }
+                "regex" => {
+                    if let Some(regex) = option.value.as_str() {
+                        filter.regex =
+                            Some(Regex::new(regex).map_err(|_| anyhow!("Invalid regex {regex}"))?);
+                    }
+                }
+                "attachments" => filter.attachments = option.value.as_bool().unwrap_or_default(),
+                "links" => filter.links = option.value.as_bool().unwrap_or_default(),
+                "bots" => filter.bots = option.value.as_bool().unwrap_or_default(),
+                "after" => filter.after = message_id(option)?,
+                "before" => filter.before = message_id(option)?,
+                _ => {}
+            }
+        }
+
+        Ok(filter)
+    }
+
+    fn matches(&self, message: &Message) -> bool {
+        self.user.is_none_or(|u| message.author.id == u)
+            && self
+                .contains
+                .as_ref()
+                .is_none_or(|c| message.content.to_lowercase().contains(c))
+            && self
+                .regex
+                .as_ref()
+                .is_none_or(|r| r.is_match(&message.content))
+            && (!self.attachments || !message.attachments.is_empty())
+            && (!self.links
+                || message.content.contains("http://")
+                || message.content.contains("https://"))
+            && (!self.bots || message.author.bot)
+    }
+
+    fn describe(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(user) = self.user {
+            parts.push(format!("from {}", user.mention()));
+        }
+        if let Some(contains) = &self.contains {
+            parts.push(format!("containing \"{contains}\""));
+        }
+        if let Some(regex) = &self.regex {
+            parts.push(format!("matching `{regex}`"));
+        }
+        if self.attachments {
+            parts.push("with attachments".to_string());
+        }
+        if self.links {
+            parts.push("with links".to_string());
+        }
+        if self.bots {
+            parts.push("from bots".to_string());
+        }
+        if let Some(after) = self.after {
+            parts.push(format!("after {after}"));
+        }
+        if let Some(before) = self.before {
+            parts.push(format!("before {before}"));
+        }
+
+        if parts.is_empty() {
+            "all messages".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
         CreateCommand::new(name)
             .description("Purge")
-            .add_option(
-                CreateCommandOption::new(
-                    CommandOptionType::SubCommand,
-                    "all",
-                    "Purge all messages",
-                )
-                .add_sub_option(
-                    CreateCommandOption::new(CommandOptionType::Integer, "amount", "Amount")
-                        .required(true)
-                        .min_int_value(1)
-                        .max_int_value(100),
-                ),
-            )
-            .add_option(
+            .add_option(filter_options(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "all",
+                "Purge all messages",
+            )))
+            .add_option(filter_options(
                 CreateCommandOption::new(
                     CommandOptionType::SubCommand,
                     "user",
@@ -38,27 +131,58 @@ pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
                 .add_sub_option(
                     CreateCommandOption::new(CommandOptionType::User, "user", "User")
                         .required(true),
-                )
-                .add_sub_option(
-                    CreateCommandOption::new(CommandOptionType::Integer, "amount", "Amount")
-                        .min_int_value(1)
-                        .max_int_value(100),
                 ),
-            )
+            ))
             .default_member_permissions(Permissions::MANAGE_MESSAGES)
             .dm_permission(false),
     );
 }
 
-pub async fn command(ctx: &Context, command: &CommandInteraction, _: &SqlitePool) -> Result<()> {
-    match command.data.options[0].name.as_str() {
-        "all" => all(ctx, command).await,
-        "user" => user(ctx, command).await,
-        _ => Err(anyhow!("Not a subcommand")),
-    }
+fn filter_options(subcommand: CreateCommandOption) -> CreateCommandOption {
+    subcommand
+        .add_sub_option(
+            CreateCommandOption::new(CommandOptionType::Integer, "amount", "Amount (100)")
+                .min_int_value(1)
+                .max_int_value(1000),
+        )
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "contains",
+            "Only messages containing this text",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "regex",
+            "Only messages matching this regex",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "attachments",
+            "Only messages with attachments",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "links",
+            "Only messages with links",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "bots",
+            "Only messages from bots",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "after",
+            "Only messages after this message id",
+        ))
+        .add_sub_option(CreateCommandOption::new(
+            CommandOptionType::String,
+            "before",
+            "Only messages before this message id",
+        ))
 }
 
-async fn all(ctx: &Context, command: &CommandInteraction) -> Result<()> {
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
     command
         .create_response(
             &ctx,
@@ -73,71 +197,145 @@ async fn all(ctx: &Context, command: &CommandInteraction) -> Result<()> {
     };
 
     let amount = options
-        .first()
-        .map(|o| o.value.as_i64().unwrap())
-        .unwrap_or(100);
+        .iter()
+        .find(|o| o.name == "amount")
+        .and_then(|o| o.value.as_i64())
+        .unwrap_or(100) as usize;
 
-    let messages = command
-        .channel_id
-        .messages(&ctx, GetMessages::new().limit(amount as u8))
-        .await?;
+    let filter = Filter::from_options(options)?;
 
-    command.channel_id.delete_messages(&ctx, &messages).await?;
+    // Bulk delete rejects messages older than 14 days
+    let cutoff = Utc::now() - Duration::days(14) + Duration::minutes(1);
 
-    command
-        .create_followup(
-            &ctx,
-            CreateInteractionResponseFollowup::new()
-                .content(format!("Purged {} messages", messages.len()))
-                .ephemeral(true),
-        )
-        .await?;
+    let mut messages = Vec::new();
+    let mut before = filter.before;
+    let mut skipped = 0;
+    let mut capped = true;
 
-    Ok(())
-}
+    'pages: for _ in 0..MAX_PAGES {
+        let mut request = GetMessages::new().limit(100);
+        if let Some(before) = before {
+            request = request.before(before);
+        }
 
-async fn user(ctx: &Context, command: &CommandInteraction) -> Result<()> {
-    command
-        .create_response(
-            &ctx,
-            CreateInteractionResponse::Defer(
-                CreateInteractionResponseMessage::new().ephemeral(true),
-            ),
-        )
-        .await?;
+        let page = command.channel_id.messages(&ctx, request).await?;
 
-    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
-        return Err(anyhow!("Not a subcommand"));
+        let Some(last) = page.last() else {
+            capped = false;
+            break;
+        };
+        before = Some(last.id);
+
+        for message in page {
+            if filter.after.is_some_and(|after| message.id <= after) {
+                capped = false;
+                break 'pages;
+            }
+
+            if !filter.matches(&message) {
+                continue;
+            }
+
+            // Bulk delete rejects these, so they are only counted
+            if *message.timestamp < cutoff {
+                skipped += 1;
+            } else {
+                messages.push(message);
+            }
+
+            if messages.len() + skipped >= amount {
+                capped = false;
+                break 'pages;
+            }
+        }
+    }
+
+    for chunk in messages.chunks(100) {
+        if let [message] = chunk {
+            message.delete(&ctx).await?;
+        } else {
+            command.channel_id.delete_messages(&ctx, chunk).await?;
+        }
+    }
+
+    let purge = database::DbPurge {
+        moderator: command.user.id.get() as i64,
+        channel: command.channel_id.get() as i64,
+        user: filter.user.map(|u| u.get() as i64),
+        count: messages.len() as i64,
+        timestamp: Utc::now().naive_utc(),
     };
+    database::set_purge(&purge, pool).await?;
 
-    let user_id = options[0].value.as_user_id().unwrap();
-    let amount = options
-        .get(1)
-        .map(|o| o.value.as_i64().unwrap())
-        .unwrap_or(100);
+    if !messages.is_empty() {
+        let mut transcript_messages = messages.clone();
+        transcript_messages.reverse();
 
-    let mut messages = command
-        .channel_id
-        .messages(&ctx, GetMessages::new())
-        .await?;
+        ChannelId::new(1209471689264603167)
+            .send_message(
+                &ctx,
+                CreateMessage::new()
+                    .embed(
+                        CreateEmbed::new()
+                            .color(0xffa500)
+                            .title(format!("Purged {} messages", messages.len()))
+                            .field("Channel", command.channel_id.mention().to_string(), true)
+                            .field("Filter", filter.describe(), true)
+                            .footer(CreateEmbedFooter::new(format!(
+                                "Purged by {}",
+                                command.user.name
+                            ))),
+                    )
+                    .add_file(CreateAttachment::bytes(
+                        updater::transcript(&transcript_messages),
+                        format!(
+                            "purge-{}-{}.txt",
+                            command.channel_id.get(),
+                            Utc::now().timestamp()
+                        ),
+                    )),
+            )
+            .await?;
+    }
 
-    messages.retain(|m| m.author.id == user_id);
-    messages.truncate(amount as usize);
+    let mut content = format!("Purged {} messages ({})", messages.len(), filter.describe());
 
-    command.channel_id.delete_messages(&ctx, &messages).await?;
+    if skipped > 0 {
+        content.push_str(&format!(
+            "\nSkipped {skipped} matching messages older than 14 days, those can't be bulk deleted"
+        ));
+    }
+
+    if capped {
+        content.push_str(&format!(
+            "\nStopped after searching {} messages",
+            MAX_PAGES * 100
+        ));
+    }
 
     command
         .create_followup(
             &ctx,
             CreateInteractionResponseFollowup::new()
-                .content(format!(
-                    "Purged {} messages from {}",
-                    messages.len(),
-                    user_id.mention()
-                ))
+                .content(content)
                 .ephemeral(true),
         )
         .await?;
 
     Ok(())
 }
+
+fn message_id(option: &CommandDataOption) -> Result<Option<MessageId>> {
+    option
+        .value
+        .as_str()
+        .map(|id| {
+            id.trim()
+                .parse::<u64>()
+                .ok()
+                .filter(|id| *id != 0)
+                .map(MessageId::new)
+                .ok_or_else(|| anyhow!("Invalid message id {id}"))
+        })
+        .transpose()
+}
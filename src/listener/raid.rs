@@ -0,0 +1,416 @@
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, CommandInteraction, CommandOptionType, ComponentInteraction,
+        Member, Mentionable, UserId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage, EditInteractionResponse,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+use tokio::sync::Mutex;
+
+use crate::{
+    database,
+    handler::{self, Raid, RaidState},
+    settings::{self, Setting},
+    GUILD_ID,
+};
+
+use super::register;
+
+const APPROVE_ID: &str = "approve";
+const KICK_ID: &str = "kick";
+const STOP_ID: &str = "stop";
+const ALL: &str = "all";
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Raid mode")
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "status",
+                "Show raid mode and held members",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "start",
+                "Start raid mode",
+            ))
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "stop",
+                "Stop raid mode",
+            ))
+            .default_member_permissions(Permissions::BAN_MEMBERS)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let raid = raid_state(ctx).await;
+    let now = Utc::now().timestamp();
+
+    let followup = match command.data.options[0].name.as_str() {
+        "status" => {
+            let until = raid.lock().await.until;
+            let held_members = database::get_held_members(pool).await?;
+
+            let mut embed = CreateEmbed::new()
+                .title("Raid mode")
+                .field(
+                    "Status",
+                    if until > now {
+                        format!("Active until <t:{until}:t>")
+                    } else {
+                        "Inactive".to_string()
+                    },
+                    false,
+                )
+                .field(
+                    format!("Held members ({})", held_members.len()),
+                    if held_members.is_empty() {
+                        "None".to_string()
+                    } else {
+                        held_members
+                            .iter()
+                            .take(25)
+                            .map(|m| format!("<@{}> - {}", m.user, m.reason))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    },
+                    false,
+                );
+
+            if held_members.len() > 25 {
+                embed = embed.footer(CreateEmbedFooter::new(format!(
+                    "And {} more",
+                    held_members.len() - 25
+                )));
+            }
+
+            let mut followup = CreateInteractionResponseFollowup::new().embed(embed);
+            if !held_members.is_empty() || until > now {
+                followup = followup.components(vec![controls()]);
+            }
+
+            followup
+        }
+        "start" => {
+            let minutes: i64 = settings::get(Setting::RaidModeMinutes, pool).await?;
+            let until = now + minutes * 60;
+
+            raid.lock().await.until = until;
+
+            CreateInteractionResponseFollowup::new().content(format!(
+                "Raid mode is active until <t:{until}:t>, new members are held"
+            ))
+        }
+        "stop" => {
+            raid.lock().await.until = 0;
+
+            CreateInteractionResponseFollowup::new()
+                .content("Raid mode stopped, held members still need to be approved or kicked")
+        }
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(&ctx, followup.ephemeral(true))
+        .await?;
+
+    Ok(())
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    if !interaction
+        .member
+        .as_ref()
+        .and_then(|m| m.permissions)
+        .is_some_and(|p| p.ban_members())
+    {
+        return Err(anyhow!("You need the ban members permission"));
+    }
+
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let action = parts.next().ok_or_else(|| anyhow!("No action"))?;
+
+    if action == STOP_ID {
+        raid_state(ctx).await.lock().await.until = 0;
+
+        return followup(ctx, interaction, "Raid mode stopped".to_string()).await;
+    }
+
+    let target = parts.next().ok_or_else(|| anyhow!("No target"))?;
+
+    let users = if target == ALL {
+        database::get_held_members(pool)
+            .await?
+            .into_iter()
+            .map(|m| UserId::new(m.user as u64))
+            .collect::<Vec<_>>()
+    } else {
+        let user = UserId::new(target.parse()?);
+
+        if database::get_held_member_by_user(user.get() as i64, pool)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow!("{} isn't held anymore", user.mention()));
+        }
+
+        vec![user]
+    };
+
+    let audit_log_reason = format!("Held during raid ({})", interaction.user.name);
+
+    let mut done = 0;
+    let mut failures = Vec::new();
+
+    // One failing member must not stop the rest of a bulk action
+    for user in &users {
+        let result = match action {
+            APPROVE_ID => approve(ctx, *user, pool).await,
+            KICK_ID => kick(ctx, *user, &audit_log_reason, pool).await,
+            _ => return Err(anyhow!("Unknown action")),
+        };
+
+        match result {
+            Ok(()) => done += 1,
+            Err(e) => failures.push(format!("{} {e}", user.mention())),
+        }
+    }
+
+    let verb = if action == APPROVE_ID {
+        "Approved"
+    } else {
+        "Kicked"
+    };
+
+    if target != ALL && done > 0 {
+        interaction
+            .edit_response(
+                &ctx,
+                EditInteractionResponse::new()
+                    .content(format!("{verb} by {}", interaction.user.mention()))
+                    .components(vec![]),
+            )
+            .await?;
+    }
+
+    let mut content = format!("{verb} {done} held members");
+
+    if !failures.is_empty() {
+        content.push_str(&format!(", {} failed:\n", failures.len()));
+        content.push_str(
+            &failures
+                .iter()
+                .take(20)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        if failures.len() > 20 {
+            content.push_str(&format!("\nAnd {} more", failures.len() - 20));
+        }
+    }
+
+    followup(ctx, interaction, content).await
+}
+
+async fn approve(ctx: &Context, user: UserId, pool: &SqlitePool) -> Result<()> {
+    // Members who left in the meantime only need to be released
+    let member = GUILD_ID.member(ctx, user).await.ok();
+
+    if let Some(member) = &member {
+        handler::add_member_roles(ctx, member, pool).await?;
+    }
+
+    database::delete_held_member_by_user(user.get() as i64, pool).await?;
+
+    if let Some(member) = &member {
+        register::welcome(ctx, member, pool).await?;
+    }
+
+    Ok(())
+}
+
+async fn kick(
+    ctx: &Context,
+    user: UserId,
+    audit_log_reason: &str,
+    pool: &SqlitePool,
+) -> Result<()> {
+    GUILD_ID
+        .kick_with_reason(ctx, user, audit_log_reason)
+        .await?;
+
+    database::delete_held_member_by_user(user.get() as i64, pool).await?;
+
+    Ok(())
+}
+
+/// Holds new members without the base role during raids or when their account is too young
+pub async fn hold_member(ctx: &Context, member: &Member, pool: &SqlitePool) -> Result<bool> {
+    let threshold: usize = settings::get(Setting::RaidJoinThreshold, pool).await?;
+    let minutes: i64 = settings::get(Setting::RaidModeMinutes, pool).await?;
+    let age_days: i64 = settings::get(Setting::RaidAccountAgeDays, pool).await?;
+    let now = Utc::now().timestamp();
+
+    let (active, triggered, until) = {
+        let raid = raid_state(ctx).await;
+        let mut raid = raid.lock().await;
+
+        raid.joins.push_back(now);
+        while raid.joins.front().is_some_and(|t| now - t >= 60) {
+            raid.joins.pop_front();
+        }
+
+        let active = raid.until > now;
+
+        if threshold > 0 && raid.joins.len() > threshold {
+            raid.until = now + minutes * 60;
+
+            (true, !active, raid.until)
+        } else {
+            (active, false, raid.until)
+        }
+    };
+
+    let age = now - member.user.id.created_at().unix_timestamp();
+
+    let reason = if active {
+        "Joined during raid mode".to_string()
+    } else if age_days > 0 && age < age_days * 24 * 60 * 60 {
+        format!("Account is younger than {age_days} days")
+    } else {
+        // A hold from an earlier join doesn't carry over
+        database::delete_held_member_by_user(member.user.id.get() as i64, pool).await?;
+
+        return Ok(false);
+    };
+
+    let held_member = database::DbHeldMember {
+        user: member.user.id.get() as i64,
+        reason: reason.clone(),
+        timestamp: Utc::now().naive_utc(),
+    };
+    database::set_held_member(&held_member, pool).await?;
+
+    let channel = ChannelId::new(1209471689264603167);
+
+    // The member is held either way, a failed alert only shows up in /raid status
+    if triggered {
+        let _ = channel
+            .send_message(
+                ctx,
+                CreateMessage::new()
+                    .embed(
+                        CreateEmbed::new()
+                            .color(0xff0000)
+                            .title("Raid mode activated")
+                            .description(format!(
+                                "More than {threshold} members joined within a minute, new members are held without roles until <t:{until}:t>"
+                            )),
+                    )
+                    .components(vec![controls()]),
+            )
+            .await;
+    } else if !active {
+        let _ = channel
+            .send_message(
+                ctx,
+                CreateMessage::new()
+                    .embed(
+                        CreateEmbed::new()
+                            .color(0xffa500)
+                            .title("Member held")
+                            .description(format!("{} - {reason}", member.mention()))
+                            .footer(CreateEmbedFooter::new(format!("User {}", member.user.id))),
+                    )
+                    .components(vec![member_controls(member.user.id)]),
+            )
+            .await;
+    }
+
+    Ok(true)
+}
+
+async fn raid_state(ctx: &Context) -> Arc<Mutex<Raid>> {
+    let data = ctx.data.read().await;
+
+    data.get::<RaidState>().unwrap().clone()
+}
+
+fn controls() -> CreateActionRow {
+    let name = super::ListenerName::Raid;
+
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{name}:{APPROVE_ID}:{ALL}"))
+            .label("Approve all held")
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("{name}:{KICK_ID}:{ALL}"))
+            .label("Kick all held")
+            .style(ButtonStyle::Danger),
+        CreateButton::new(format!("{name}:{STOP_ID}"))
+            .label("Stop raid mode")
+            .style(ButtonStyle::Secondary),
+    ])
+}
+
+fn member_controls(user: UserId) -> CreateActionRow {
+    let name = super::ListenerName::Raid;
+
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{name}:{APPROVE_ID}:{user}"))
+            .label("Approve")
+            .style(ButtonStyle::Success),
+        CreateButton::new(format!("{name}:{KICK_ID}:{user}"))
+            .label("Kick")
+            .style(ButtonStyle::Danger),
+    ])
+}
+
+async fn followup(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    content: String,
+) -> Result<()> {
+    interaction
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
@@ -3,22 +3,35 @@ use chrono::Utc;
 use rand::{distr::Alphanumeric, Rng};
 use serenity::{
     all::{
-        ActionRowComponent, CommandInteraction, CommandOptionType, ComponentInteraction,
-        InputTextStyle, Mentionable, ModalInteraction, UserId,
+        ActionRowComponent, ButtonStyle, ChannelId, CommandInteraction, CommandOptionType,
+        ComponentInteraction, InputTextStyle, Member, Mentionable, ModalInteraction, UserId,
     },
     builder::{
-        CreateActionRow, CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
-        CreateInputText, CreateInteractionResponse, CreateInteractionResponseFollowup,
-        CreateInteractionResponseMessage, CreateModal,
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateEmbedFooter, CreateInputText, CreateInteractionResponse,
+        CreateInteractionResponseFollowup, CreateInteractionResponseMessage, CreateMessage,
+        CreateModal,
     },
     client::Context,
 };
 use sqlx::SqlitePool;
 
-use crate::{database, stardb};
+use crate::{
+    database,
+    settings::{self, Setting},
+    stardb,
+};
 
 const UID_ID: &str = "uid";
 
+pub fn button() -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(
+        super::ListenerName::Register.to_string(),
+    )
+    .label("Register")
+    .style(ButtonStyle::Primary)])
+}
+
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
     commands.push(
         CreateCommand::new(name)
@@ -161,6 +174,55 @@ pub async fn component(
     Ok(())
 }
 
+pub async fn welcome(ctx: &Context, member: &Member, pool: &SqlitePool) -> Result<()> {
+    // Held members are welcomed once they are approved
+    if database::get_held_member_by_user(member.user.id.get() as i64, pool)
+        .await?
+        .is_some()
+    {
+        return Ok(());
+    }
+
+    let channel: u64 = settings::get(Setting::WelcomeChannel, pool).await?;
+    let dm: i64 = settings::get(Setting::WelcomeDm, pool).await?;
+
+    if channel == 0 && dm == 0 {
+        return Ok(());
+    }
+
+    let message: String = settings::get(Setting::WelcomeMessage, pool).await?;
+
+    let embed = CreateEmbed::new()
+        .title("Welcome")
+        .thumbnail("https://cdn.discordapp.com/emojis/1112854178302267452.png")
+        .description(message.replace("{user}", &member.mention().to_string()));
+
+    if channel != 0 {
+        ChannelId::new(channel)
+            .send_message(
+                ctx,
+                CreateMessage::new()
+                    .content(member.mention().to_string())
+                    .embed(embed.clone())
+                    .components(vec![button()]),
+            )
+            .await?;
+    }
+
+    if dm != 0 {
+        // Members with closed dms still get the channel message
+        let _ = member
+            .user
+            .direct_message(
+                ctx,
+                CreateMessage::new().embed(embed).components(vec![button()]),
+            )
+            .await;
+    }
+
+    Ok(())
+}
+
 fn response(otp: &str) -> CreateInteractionResponseFollowup {
     let text = format!("Please verify that your UID belongs to you by appending the following 6 characters to your bio of your player account in game. The bio must have the 6 characters last.\n\n**{otp}**\n\nThen wait 5 - 15 mins. The bot will verify your ownership and add your account's achievements to the rankings. Once you are added, you're free to change your comment section.\n\nIf you encounter an issue, please message us in the <#1010268018028327062> channel.");
 
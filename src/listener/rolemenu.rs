@@ -0,0 +1,517 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, CommandDataOption, CommandDataOptionValue, CommandInteraction,
+        CommandOptionType, ComponentInteraction, ComponentInteractionDataKind, Mentionable,
+        MessageId, RoleId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage, CreateSelectMenu, CreateSelectMenuKind,
+        CreateSelectMenuOption, EditMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::database;
+
+const BUTTONS: &str = "buttons";
+const SELECT: &str = "select";
+const MAX_ROLES: usize = 25;
+/// Discord limits button labels to 80 characters
+const MAX_LABEL_LENGTH: usize = 80;
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Self-assignable role menus")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "create",
+                    "Post a new role menu in this channel",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "title", "Title")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "kind", "Kind (buttons)")
+                        .add_string_choice("Buttons", BUTTONS)
+                        .add_string_choice("Select menu", SELECT),
+                )
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "description",
+                    "Description",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Boolean,
+                    "exclusive",
+                    "Members can only have one role of this menu",
+                ))
+                .add_sub_option(CreateCommandOption::new(
+                    CommandOptionType::Role,
+                    "required",
+                    "Role members need to use this menu",
+                ))
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::Integer,
+                        "max",
+                        "Maximum roles of this menu per member",
+                    )
+                    .min_int_value(1)
+                    .max_int_value(MAX_ROLES as u64),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "add",
+                    "Add a role to a menu",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "menu", "Menu id")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(
+                        CommandOptionType::String,
+                        "label",
+                        "Label (role name)",
+                    )
+                    .max_length(MAX_LABEL_LENGTH as u16),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "remove",
+                    "Remove a role from a menu",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "menu", "Menu id")
+                        .required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "delete", "Delete a menu")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::Integer, "menu", "Menu id")
+                            .required(true),
+                    ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List menus",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let content = match command.data.options[0].name.as_str() {
+        "create" => create(ctx, command, options, pool).await?,
+        "add" => {
+            let values = values(options);
+            let menu = menu(values["menu"].as_i64().unwrap(), pool).await?;
+            let role = values["role"].as_role_id().unwrap();
+
+            let roles = database::get_role_menu_roles_by_menu(menu.id, pool).await?;
+            if roles.len() >= MAX_ROLES && !roles.iter().any(|r| r.role == role.get() as i64) {
+                return Err(anyhow!("Menus can have at most {MAX_ROLES} roles"));
+            }
+
+            let label = match values.get("label").and_then(|v| v.as_str()) {
+                Some(label) => label.to_string(),
+                // Role names can be longer than button labels
+                None => command
+                    .data
+                    .resolved
+                    .roles
+                    .get(&role)
+                    .map(|r| r.name.chars().take(MAX_LABEL_LENGTH).collect())
+                    .ok_or_else(|| anyhow!("Unknown role"))?,
+            };
+
+            let menu_role = database::DbRoleMenuRole {
+                menu: menu.id,
+                role: role.get() as i64,
+                label,
+            };
+            database::set_role_menu_role(&menu_role, pool).await?;
+
+            update(ctx, &menu, pool).await?;
+
+            format!("Added {} to menu {}", role.mention(), menu.id)
+        }
+        "remove" => {
+            let values = values(options);
+            let menu = menu(values["menu"].as_i64().unwrap(), pool).await?;
+            let role = values["role"].as_role_id().unwrap();
+
+            database::delete_role_menu_role_by_menu_and_role(menu.id, role.get() as i64, pool)
+                .await?;
+
+            update(ctx, &menu, pool).await?;
+
+            format!("Removed {} from menu {}", role.mention(), menu.id)
+        }
+        "delete" => {
+            let menu = menu(options[0].value.as_i64().unwrap(), pool).await?;
+
+            let _ = ChannelId::new(menu.channel as u64)
+                .delete_message(ctx, MessageId::new(menu.message as u64))
+                .await;
+
+            database::delete_role_menu_by_id(menu.id, pool).await?;
+
+            format!("Deleted menu {}", menu.id)
+        }
+        "list" => {
+            let menus = database::get_role_menus(pool).await?;
+
+            if menus.is_empty() {
+                "No role menus".to_string()
+            } else {
+                menus
+                    .iter()
+                    .map(|m| {
+                        format!(
+                            "{} - {} ({}) - {}",
+                            m.id,
+                            m.title,
+                            m.kind,
+                            MessageId::new(m.message as u64)
+                                .link(ChannelId::new(m.channel as u64), command.guild_id)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn create(
+    ctx: &Context,
+    command: &CommandInteraction,
+    options: &[CommandDataOption],
+    pool: &SqlitePool,
+) -> Result<String> {
+    let values = values(options);
+
+    let mut menu = database::DbRoleMenu {
+        id: 0,
+        channel: command.channel_id.get() as i64,
+        message: 0,
+        title: values["title"].as_str().unwrap().to_string(),
+        description: values
+            .get("description")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        kind: values
+            .get("kind")
+            .and_then(|v| v.as_str())
+            .unwrap_or(BUTTONS)
+            .to_string(),
+        exclusive: values
+            .get("exclusive")
+            .and_then(|v| v.as_bool())
+            .unwrap_or_default(),
+        required: values
+            .get("required")
+            .and_then(|v| v.as_role_id())
+            .map(|r| r.get() as i64),
+        max: values.get("max").and_then(|v| v.as_i64()),
+    };
+
+    let message = command
+        .channel_id
+        .send_message(ctx, CreateMessage::new().embed(embed(&menu, &[])))
+        .await?;
+
+    menu.message = message.id.get() as i64;
+    let id = database::set_role_menu(&menu, pool).await?;
+
+    Ok(format!(
+        "Created menu {id}, add roles with /rolemenu add menu:{id}"
+    ))
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let id: i64 = parts.next().ok_or_else(|| anyhow!("No menu"))?.parse()?;
+
+    let menu = menu(id, pool).await?;
+    let roles = database::get_role_menu_roles_by_menu(menu.id, pool)
+        .await?
+        .into_iter()
+        .map(|r| RoleId::new(r.role as u64))
+        .collect::<Vec<_>>();
+
+    let member = interaction
+        .member
+        .as_ref()
+        .ok_or_else(|| anyhow!("Role menus only work in the server"))?;
+
+    if let Some(required) = menu.required {
+        if !member.roles.contains(&RoleId::new(required as u64)) {
+            return Err(anyhow!(
+                "You need the {} role to use this menu",
+                RoleId::new(required as u64).mention()
+            ));
+        }
+    }
+
+    let current = member
+        .roles
+        .iter()
+        .filter(|r| roles.contains(r))
+        .copied()
+        .collect::<HashSet<_>>();
+
+    // Discord doesn't preselect the member's roles, so selected options toggle like buttons
+    let selected = match &interaction.data.kind {
+        ComponentInteractionDataKind::Button => {
+            vec![RoleId::new(
+                parts.next().ok_or_else(|| anyhow!("No role"))?.parse()?,
+            )]
+        }
+        ComponentInteractionDataKind::StringSelect { values } => values
+            .iter()
+            .filter_map(|v| v.parse().ok().map(RoleId::new))
+            .collect(),
+        _ => return Err(anyhow!("Unknown component")),
+    };
+
+    let mut wanted = current.clone();
+
+    for role in selected {
+        if !roles.contains(&role) {
+            return Err(anyhow!("This role isn't part of the menu anymore"));
+        }
+
+        if wanted.contains(&role) {
+            wanted.remove(&role);
+        } else {
+            if menu.exclusive {
+                wanted.clear();
+            }
+
+            wanted.insert(role);
+        }
+    }
+
+    // Removing roles is always allowed, even when a member is above the limit
+    if let Some(max) = menu.max {
+        if wanted.len() > max as usize && !wanted.is_subset(&current) {
+            return Err(anyhow!("You can only pick {max} roles from this menu"));
+        }
+    }
+
+    let mut added = Vec::new();
+    let mut removed = Vec::new();
+
+    for role in wanted.difference(&current) {
+        member.add_role(ctx, *role).await?;
+        added.push(role.mention().to_string());
+    }
+
+    for role in current.difference(&wanted) {
+        member.remove_role(ctx, *role).await?;
+        removed.push(role.mention().to_string());
+    }
+
+    let mut content = Vec::new();
+    if !added.is_empty() {
+        content.push(format!("Added {}", added.join(", ")));
+    }
+    if !removed.is_empty() {
+        content.push(format!("Removed {}", removed.join(", ")));
+    }
+    if content.is_empty() {
+        content.push("Nothing changed".to_string());
+    }
+
+    interaction
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content.join("\n"))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn menu(id: i64, pool: &SqlitePool) -> Result<database::DbRoleMenu> {
+    database::get_role_menu_by_id(id, pool)
+        .await?
+        .ok_or_else(|| anyhow!("Menu {id} doesn't exist"))
+}
+
+fn values(options: &[CommandDataOption]) -> HashMap<&str, &CommandDataOptionValue> {
+    options
+        .iter()
+        .map(|o| (o.name.as_str(), &o.value))
+        .collect()
+}
+
+async fn update(ctx: &Context, menu: &database::DbRoleMenu, pool: &SqlitePool) -> Result<()> {
+    let roles = database::get_role_menu_roles_by_menu(menu.id, pool).await?;
+
+    ChannelId::new(menu.channel as u64)
+        .edit_message(
+            ctx,
+            MessageId::new(menu.message as u64),
+            EditMessage::new()
+                .embed(embed(menu, &roles))
+                .components(components(menu, &roles)),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn embed(menu: &database::DbRoleMenu, roles: &[database::DbRoleMenuRole]) -> CreateEmbed {
+    let mut description = Vec::new();
+
+    if !menu.description.is_empty() {
+        description.push(menu.description.clone());
+    }
+
+    if !roles.is_empty() {
+        description.push(
+            roles
+                .iter()
+                .map(|r| format!("<@&{}> - {}", r.role, r.label))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    }
+
+    let mut rules = Vec::new();
+    if menu.exclusive {
+        rules.push("Pick one role".to_string());
+    } else if let Some(max) = menu.max {
+        rules.push(format!("Pick up to {max} roles"));
+    }
+    if let Some(required) = menu.required {
+        rules.push(format!("Requires <@&{required}>"));
+    }
+    if !rules.is_empty() {
+        description.push(rules.join(" - "));
+    }
+
+    CreateEmbed::new()
+        .title(&menu.title)
+        .description(description.join("\n\n"))
+}
+
+fn components(
+    menu: &database::DbRoleMenu,
+    roles: &[database::DbRoleMenuRole],
+) -> Vec<CreateActionRow> {
+    if roles.is_empty() {
+        return Vec::new();
+    }
+
+    let name = super::ListenerName::Rolemenu;
+
+    if menu.kind == SELECT {
+        let max = if menu.exclusive {
+            1
+        } else {
+            menu.max
+                .map_or(roles.len(), |m| (m as usize).min(roles.len()))
+        };
+
+        return vec![CreateActionRow::SelectMenu(
+            CreateSelectMenu::new(
+                format!("{name}:{}", menu.id),
+                CreateSelectMenuKind::String {
+                    options: roles
+                        .iter()
+                        .map(|r| CreateSelectMenuOption::new(&r.label, r.role.to_string()))
+                        .collect(),
+                },
+            )
+            .placeholder("Pick roles to add or remove")
+            .min_values(1)
+            .max_values(max as u8),
+        )];
+    }
+
+    roles
+        .chunks(5)
+        .map(|chunk| {
+            CreateActionRow::Buttons(
+                chunk
+                    .iter()
+                    .map(|r| {
+                        CreateButton::new(format!("{name}:{}:{}", menu.id, r.role))
+                            .label(&r.label)
+                            .style(ButtonStyle::Secondary)
+                    })
+                    .collect(),
+            )
+        })
+        .collect()
+}
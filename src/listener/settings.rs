@@ -0,0 +1,277 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use serenity::{
+    all::{CommandDataOptionValue, CommandInteraction, CommandOptionType},
+    builder::{
+        CreateAutocompleteResponse, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+use strum::IntoEnumIterator;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+};
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Bot settings")
+            .add_option(
+                CreateCommandOption::new(CommandOptionType::SubCommand, "set", "Set a setting")
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "key", "Key")
+                            .required(true)
+                            .set_autocomplete(true),
+                    )
+                    .add_sub_option(
+                        CreateCommandOption::new(CommandOptionType::String, "value", "Value")
+                            .required(true),
+                    ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "reset",
+                    "Reset a setting to its default",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "key", "Key")
+                        .required(true)
+                        .set_autocomplete(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List settings",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    match command.data.options[0].name.as_str() {
+        "set" => set(ctx, command, pool).await,
+        "reset" => reset(ctx, command, pool).await,
+        "list" => list(ctx, command, pool).await,
+        _ => Err(anyhow!("Not a subcommand")),
+    }
+}
+
+pub async fn autocomplete(
+    ctx: &Context,
+    autocomplete: &CommandInteraction,
+    _: &SqlitePool,
+) -> Result<()> {
+    let input = autocomplete
+        .data
+        .autocomplete()
+        .map(|o| o.value.to_lowercase())
+        .unwrap_or_default();
+
+    let mut response = CreateAutocompleteResponse::new();
+
+    for setting in Setting::iter()
+        .map(|s| s.to_string())
+        .filter(|s| s.to_lowercase().contains(&input))
+        .take(25)
+    {
+        response = response.add_string_choice(&setting, &setting);
+    }
+
+    autocomplete
+        .create_response(&ctx, CreateInteractionResponse::Autocomplete(response))
+        .await?;
+
+    Ok(())
+}
+
+async fn set(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let key = options[0].value.as_str().unwrap();
+    let value = options[1].value.as_str().unwrap().trim();
+
+    let setting = Setting::from_str(key).map_err(|_| anyhow!("Unknown setting {key}"))?;
+
+    validate(setting, value, pool).await?;
+
+    let db_setting = database::DbSetting {
+        key: setting.to_string(),
+        value: value.to_string(),
+    };
+    database::set_setting(&db_setting, pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!("Set {setting} to {value}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn reset(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let key = options[0].value.as_str().unwrap();
+
+    let setting = Setting::from_str(key).map_err(|_| anyhow!("Unknown setting {key}"))?;
+
+    validate(setting, setting.default(), pool).await?;
+
+    database::delete_setting_by_key(&setting.to_string(), pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!("Reset {setting} to {}", setting.default()))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn validate(setting: Setting, value: &str, pool: &SqlitePool) -> Result<()> {
+    if !parses(setting, value) {
+        return Err(anyhow!("{setting} has to be a number of at least 0"));
+    }
+
+    match setting {
+        Setting::MatchIdleWarnHours | Setting::MatchIdleDisbandHours => {
+            let (warn, disband): (i64, i64) = if let Setting::MatchIdleWarnHours = setting {
+                (
+                    value.parse()?,
+                    settings::get(Setting::MatchIdleDisbandHours, pool).await?,
+                )
+            } else {
+                (
+                    settings::get(Setting::MatchIdleWarnHours, pool).await?,
+                    value.parse()?,
+                )
+            };
+
+            if warn >= disband {
+                return Err(anyhow!(
+                    "{} ({warn}) has to be lower than {} ({disband})",
+                    Setting::MatchIdleWarnHours,
+                    Setting::MatchIdleDisbandHours
+                ));
+            }
+        }
+        Setting::MatchCategoryLimit if value.parse::<usize>() == Ok(0) => {
+            return Err(anyhow!("{setting} has to be at least 1"));
+        }
+        Setting::AppealChannel if value == "0" => {
+            return Err(anyhow!("{setting} has to be a channel"));
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn list(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let db_settings = database::get_settings(pool).await?;
+
+    let message = Setting::iter()
+        .map(|setting| {
+            let key = setting.to_string();
+
+            let value = db_settings
+                .iter()
+                .find(|s| s.key == key)
+                .map(|s| s.value.clone())
+                .unwrap_or_else(|| format!("{} (default)", setting.default()));
+
+            format!("**{key}** - {value}\n{}", setting.description())
+        })
+        .collect::<Vec<_>>();
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(
+                    CreateEmbed::new()
+                        .title("Settings")
+                        .description(message.join("\n\n")),
+                )
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Whether the value parses as the type the setting is read as
+fn parses(setting: Setting, value: &str) -> bool {
+    match setting {
+        Setting::MatchCategoryName | Setting::WelcomeMessage => true,
+        Setting::MatchCategoryLimit => value.parse::<usize>().is_ok(),
+        Setting::MatchArchiveChannel
+        | Setting::AppealChannel
+        | Setting::MessageLogChannel
+        | Setting::AutomodLogChannel
+        | Setting::WelcomeChannel => value.parse::<u64>().is_ok(),
+        Setting::MatchIdleWarnHours
+        | Setting::MatchIdleDisbandHours
+        | Setting::WarnMinorExpiryDays
+        | Setting::WarnModerateExpiryDays
+        | Setting::WarnSevereExpiryDays
+        | Setting::MessageCachePersistent
+        | Setting::MessageCacheChannelLimit
+        | Setting::MessageCacheRetentionDays
+        | Setting::BlacklistWarnThreshold
+        | Setting::BlacklistWarnDays
+        | Setting::RaidJoinThreshold
+        | Setting::RaidAccountAgeDays
+        | Setting::RaidModeMinutes
+        | Setting::RolePersistRetentionDays
+        | Setting::WelcomeDm => value.parse::<i64>().is_ok_and(|v| v >= 0),
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::HashSet;
+
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serenity::{
+    all::{
+        ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType,
+        GuildMemberUpdateEvent, Member, MemberAction, Mentionable, RoleId, UserId,
+    },
+    builder::{
+        CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
+        CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::{database, GUILD_ID};
+
+use super::modstats::audit_log_entry;
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Punishment roles that are always restored on rejoin")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "add",
+                    "Make a role sticky",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "remove",
+                    "Stop a role from being sticky",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Role, "role", "Role")
+                        .required(true),
+                ),
+            )
+            .add_option(CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "list",
+                "List sticky roles",
+            ))
+            .default_member_permissions(Permissions::ADMINISTRATOR)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let content = match command.data.options[0].name.as_str() {
+        "add" => {
+            let role = options[0].value.as_role_id().unwrap();
+
+            let sticky_role = database::DbStickyRole {
+                role: role.get() as i64,
+            };
+            database::set_sticky_role(&sticky_role, pool).await?;
+
+            // Members who already have the role keep it when they rejoin
+            let mut count = 0;
+            let mut after = None;
+
+            loop {
+                let members = GUILD_ID.members(ctx, Some(1000), after).await?;
+
+                let Some(last) = members.last() else {
+                    break;
+                };
+                after = Some(last.user.id);
+
+                for member in members.iter().filter(|m| m.roles.contains(&role)) {
+                    let user = member.user.id.get() as i64;
+
+                    if database::get_sticky_assignment_by_user_and_role(
+                        user,
+                        role.get() as i64,
+                        pool,
+                    )
+                    .await?
+                    .is_none()
+                    {
+                        let assignment = database::DbStickyAssignment {
+                            user,
+                            role: role.get() as i64,
+                            moderator: None,
+                            timestamp: Utc::now().naive_utc(),
+                        };
+                        database::set_sticky_assignment(&assignment, pool).await?;
+                    }
+
+                    count += 1;
+                }
+            }
+
+            format!("{} is sticky now, {count} members have it", role.mention())
+        }
+        "remove" => {
+            let role = options[0].value.as_role_id().unwrap();
+
+            database::delete_sticky_role_by_role(role.get() as i64, pool).await?;
+
+            format!("{} isn't sticky anymore", role.mention())
+        }
+        "list" => {
+            let sticky_roles = database::get_sticky_roles(pool).await?;
+
+            if sticky_roles.is_empty() {
+                "No roles are sticky".to_string()
+            } else {
+                sticky_roles
+                    .iter()
+                    .map(|r| format!("<@&{}>", r.role))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+        }
+        _ => return Err(anyhow!("Not a subcommand")),
+    };
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(content)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub async fn member_update(
+    ctx: &Context,
+    old_roles: &[RoleId],
+    event: &GuildMemberUpdateEvent,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let sticky_roles = database::get_sticky_roles(pool)
+        .await?
+        .into_iter()
+        .map(|r| RoleId::new(r.role as u64))
+        .collect::<HashSet<_>>();
+
+    let user = event.user.id.get() as i64;
+
+    for role in old_roles
+        .iter()
+        .filter(|r| sticky_roles.contains(r) && !event.roles.contains(r))
+    {
+        database::delete_sticky_assignment_by_user_and_role(user, role.get() as i64, pool).await?;
+    }
+
+    for role in event
+        .roles
+        .iter()
+        .filter(|r| sticky_roles.contains(r) && !old_roles.contains(r))
+    {
+        // Restoring a sticky role on rejoin keeps the original assignment
+        if database::get_sticky_assignment_by_user_and_role(user, role.get() as i64, pool)
+            .await?
+            .is_some()
+        {
+            continue;
+        }
+
+        let moderator = audit_log_entry(ctx, &GUILD_ID, event.user.id, MemberAction::RoleUpdate)
+            .await
+            .map(|e| e.user_id.get() as i64);
+
+        let assignment = database::DbStickyAssignment {
+            user,
+            role: role.get() as i64,
+            moderator,
+            timestamp: Utc::now().naive_utc(),
+        };
+        database::set_sticky_assignment(&assignment, pool).await?;
+    }
+
+    Ok(())
+}
+
+pub async fn member_addition(ctx: &Context, member: &Member, pool: &SqlitePool) -> Result<()> {
+    let sticky_roles = database::get_sticky_roles(pool)
+        .await?
+        .into_iter()
+        .map(|r| r.role)
+        .collect::<HashSet<_>>();
+
+    for assignment in
+        database::get_sticky_assignments_by_user(member.user.id.get() as i64, pool).await?
+    {
+        if !sticky_roles.contains(&assignment.role) {
+            continue;
+        }
+
+        let role = RoleId::new(assignment.role as u64);
+
+        // Sticky roles skip the position check of the regular role restoration
+        let restored = member.add_role(ctx, role).await;
+
+        let applied_by = match assignment.moderator {
+            Some(moderator) => UserId::new(moderator as u64).mention().to_string(),
+            None => "Unknown".to_string(),
+        };
+
+        let embed = match restored {
+            Ok(()) => CreateEmbed::new()
+                .color(0xffa500)
+                .title("Sticky role restored")
+                .description(format!(
+                    "{} rejoined and got {} back",
+                    member.mention(),
+                    role.mention()
+                )),
+            Err(e) => CreateEmbed::new()
+                .color(0xff0000)
+                .title("Sticky role not restored")
+                .description(format!(
+                    "{} rejoined but {} couldn't be restored: {e}",
+                    member.mention(),
+                    role.mention()
+                )),
+        };
+
+        // The remaining sticky roles still have to be restored if the log fails
+        let _ = ChannelId::new(1209471689264603167)
+            .send_message(
+                ctx,
+                CreateMessage::new().embed(
+                    embed
+                        .field("Applied by", applied_by, true)
+                        .field(
+                            "Applied",
+                            format!("<t:{}:R>", assignment.timestamp.and_utc().timestamp()),
+                            true,
+                        )
+                        .footer(CreateEmbedFooter::new(format!("User {}", member.user.id))),
+                ),
+            )
+            .await;
+    }
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
 use serenity::{
     all::{
         ActionRowComponent, ChannelId, CommandInteraction, CommandOptionType, CommandType,
@@ -7,25 +8,62 @@ use serenity::{
     },
     builder::{
         CreateCommand, CreateCommandOption, CreateEmbed, CreateEmbedFooter,
-        CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage,
+        CreateInteractionResponse, CreateInteractionResponseFollowup, CreateMessage, EditMessage,
     },
     client::Context,
     model::Permissions,
 };
 use sqlx::SqlitePool;
+use strum::IntoEnumIterator;
 
-use std::collections::HashMap;
+use std::{collections::HashMap, str::FromStr};
 
-use crate::{database, handler::MessageCache};
+use crate::{
+    database, handler,
+    settings::{self, Setting},
+};
+
+use super::{
+    appeals::{self, AppealKind},
+    case::{self, CaseKind},
+};
 
 #[derive(serde::Serialize, serde::Deserialize)]
-struct WarnedMessage {
-    content: String,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
-    attachments: Vec<String>,
+pub struct WarnedMessage {
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<String>,
+}
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString)]
+#[strum(serialize_all = "lowercase", ascii_case_insensitive)]
+pub enum Severity {
+    Minor,
+    Moderate,
+    Severe,
+}
+
+impl Severity {
+    async fn expires(&self, pool: &SqlitePool) -> Result<Option<NaiveDateTime>> {
+        let setting = match self {
+            Severity::Minor => Setting::WarnMinorExpiryDays,
+            Severity::Moderate => Setting::WarnModerateExpiryDays,
+            Severity::Severe => Setting::WarnSevereExpiryDays,
+        };
+
+        let days: i64 = settings::get(setting, pool).await?;
+
+        Ok((days > 0).then(|| (Utc::now() + Duration::days(days)).naive_utc()))
+    }
 }
 
 pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    let mut severity =
+        CreateCommandOption::new(CommandOptionType::String, "severity", "Severity (moderate)");
+    for s in Severity::iter() {
+        severity = severity.add_string_choice(s.to_string(), s.to_string());
+    }
+
     commands.push(
         CreateCommand::new(name)
             .add_option(
@@ -35,6 +73,7 @@ pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
                 CreateCommandOption::new(CommandOptionType::String, "reason", "Reason")
                     .required(true),
             )
+            .add_option(severity)
             .description("Warn a user")
             .default_member_permissions(Permissions::MANAGE_NICKNAMES)
             .dm_permission(false),
@@ -79,6 +118,11 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
 
     let user = values["user"].as_user_id().unwrap();
     let reason = values["reason"].as_str().unwrap().to_string();
+    let severity = values
+        .get("severity")
+        .and_then(|v| v.as_str())
+        .and_then(|s| Severity::from_str(s).ok())
+        .unwrap_or(Severity::Moderate);
 
     if user == 246684413075652612 {
         command
@@ -93,7 +137,7 @@ pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqliteP
         return Ok(());
     }
 
-    warn(ctx, user, &reason, None, &command.user, pool).await?;
+    warn(ctx, user, &reason, severity, None, &command.user, pool).await?;
 
     command
         .create_followup(
@@ -137,33 +181,33 @@ pub async fn modal(ctx: &Context, interaction: &ModalInteraction, pool: &SqliteP
     }
 
     let reason = inputs["reason"].clone();
+    let severity = match inputs.get("severity") {
+        Some(severity) => Severity::from_str(severity.trim())
+            .map_err(|_| anyhow!("Severity has to be minor, moderate or severe"))?,
+        None => Severity::Moderate,
+    };
     let (warned_message, message) = match (inputs.get("message"), inputs.get("channel")) {
         (Some(message_id), Some(channel_id)) => {
             let channel_id = ChannelId::new(channel_id.parse()?);
             let message_id = MessageId::new(message_id.parse()?);
 
-            let message = {
-                let message_cache_lock = {
-                    let data = ctx.data.read().await;
-
-                    data.get::<MessageCache>().unwrap().clone()
-                };
-
-                let message_cache = message_cache_lock.lock().await;
-
-                if let Some(message) = message_cache.get(&(channel_id.get(), message_id.get())) {
-                    message.clone()
-                } else {
-                    channel_id.message(&ctx, message_id).await?
-                }
-            };
+            let message = cached_message(ctx, channel_id, message_id, pool).await?;
 
             (Some(message_to_warned_message(&message)), Some(message))
         }
         _ => (None, None),
     };
 
-    warn(ctx, user, &reason, warned_message, &interaction.user, pool).await?;
+    warn(
+        ctx,
+        user,
+        &reason,
+        severity,
+        warned_message,
+        &interaction.user,
+        pool,
+    )
+    .await?;
 
     if let Some(message) = message {
         let _ = message.delete(&ctx).await;
@@ -181,23 +225,24 @@ pub async fn modal(ctx: &Context, interaction: &ModalInteraction, pool: &SqliteP
     Ok(())
 }
 
-async fn warn(
+pub async fn warn(
     ctx: &Context,
     user: UserId,
     reason: &str,
+    severity: Severity,
     warned_message: Option<WarnedMessage>,
     moderator: &User,
     pool: &SqlitePool,
 ) -> Result<()> {
     let dm_channel = user.create_dm_channel(ctx).await?;
 
-    let dmed = dm_channel
+    let dm = dm_channel
         .send_message(
             ctx,
             CreateMessage::new().content(format!("You have been warned for: {}", reason)),
         )
-        .await
-        .is_ok();
+        .await;
+    let dmed = dm.is_ok();
 
     let create_message = match &warned_message {
         Some(warned_message) => Some(warned_message_to_create_message(ctx, warned_message).await),
@@ -217,41 +262,70 @@ async fn warn(
     let channel = ChannelId::new(1209471689264603167);
 
     let db_warn = database::DbWarn {
+        id: 0,
         user: user.id.get() as i64,
         moderator: moderator.id.get() as i64,
         reason: reason.to_string(),
         dm: dmed,
         message: warned_message.map(|m| serde_json::to_string(&m).unwrap()),
+        timestamp: Utc::now().naive_utc(),
+        severity: severity.to_string(),
+        expires: severity.expires(pool).await?,
     };
+    let expires = db_warn.expires;
 
     let id = database::set_warn(db_warn, pool).await?;
 
-    channel
-        .send_message(
-            ctx,
-            CreateMessage::new().embed(
-                CreateEmbed::default()
-                    .color(0xff0000)
-                    .title(format!("Warned {}!", user.name))
-                    .field("Id", id.to_string(), true)
-                    .field("User", user.mention().to_string(), true)
-                    .field("Reason", reason, true)
-                    .field("Dm", if dmed { "✅" } else { "❌" }, true)
-                    .field(
-                        "Message",
-                        if create_message.is_some() {
-                            "✅"
-                        } else {
-                            "❌"
-                        },
-                        true,
-                    )
-                    .footer(CreateEmbedFooter::new(format!(
-                        "Warned by {}",
-                        moderator.name
-                    ))),
-            ),
+    if let Ok(mut dm) = dm {
+        let _ = dm
+            .edit(
+                ctx,
+                EditMessage::new().components(vec![appeals::button(AppealKind::Warn, id)]),
+            )
+            .await;
+    }
+
+    // The warning is already recorded, so a failed escalation must not skip the log
+    let action = match escalate(ctx, user.id, pool).await {
+        Ok(action) => action,
+        Err(why) => Some(format!("Failed: {why}")),
+    };
+
+    let mut embed = CreateEmbed::default()
+        .color(0xff0000)
+        .title(format!("Warned {}!", user.name))
+        .field("Id", id.to_string(), true)
+        .field("User", user.mention().to_string(), true)
+        .field("Reason", reason, true)
+        .field("Severity", severity.to_string(), true)
+        .field(
+            "Expires",
+            expires
+                .map(|e| format!("<t:{}:R>", e.and_utc().timestamp()))
+                .unwrap_or("Never".to_string()),
+            true,
         )
+        .field("Dm", if dmed { "✅" } else { "❌" }, true)
+        .field(
+            "Message",
+            if create_message.is_some() {
+                "✅"
+            } else {
+                "❌"
+            },
+            true,
+        )
+        .footer(CreateEmbedFooter::new(format!(
+            "Warned by {}",
+            moderator.name
+        )));
+
+    if let Some(action) = action {
+        embed = embed.field("Automatic action", action, false);
+    }
+
+    channel
+        .send_message(ctx, CreateMessage::new().embed(embed))
         .await?;
 
     if let Some(create_message) = create_message.clone() {
@@ -264,6 +338,71 @@ async fn warn(
     Ok(())
 }
 
+async fn escalate(ctx: &Context, user: UserId, pool: &SqlitePool) -> Result<Option<String>> {
+    let now = Utc::now();
+
+    for escalation in database::get_escalations_order_by_warns_desc(pool).await? {
+        let count = database::get_active_warn_count_by_user_since(
+            user.get() as i64,
+            (now - Duration::days(escalation.days)).naive_utc(),
+            now.naive_utc(),
+            pool,
+        )
+        .await?;
+
+        if count < escalation.warns {
+            continue;
+        }
+
+        let reason = format!("{count} warnings in {} days", escalation.days);
+
+        let (kind, duration, action) = match escalation.action.as_str() {
+            "timeout" => {
+                let minutes = escalation.duration.unwrap_or(60);
+                (
+                    CaseKind::Timeout,
+                    Some(minutes),
+                    format!("Timed out for {} ({reason})", format_minutes(minutes)),
+                )
+            }
+            "ban" => (CaseKind::Ban, None, format!("Banned ({reason})")),
+            _ => continue,
+        };
+
+        // Going through the case keeps the dm, appeal button and mod log of manual actions
+        let moderator = User::clone(&ctx.cache.current_user());
+        let db_case = case::new_case(kind, user, reason, duration, None, &moderator);
+        case::execute(ctx, kind, db_case, &moderator, pool).await?;
+
+        return Ok(Some(action));
+    }
+
+    Ok(None)
+}
+
+pub async fn cached_message(
+    ctx: &Context,
+    channel_id: ChannelId,
+    message_id: MessageId,
+    pool: &SqlitePool,
+) -> Result<Message> {
+    if let Some(message) = handler::get_cached_message(ctx, channel_id, message_id, pool).await? {
+        Ok(message)
+    } else {
+        Ok(channel_id.message(&ctx, message_id).await?)
+    }
+}
+
+pub fn format_minutes(minutes: i64) -> String {
+    if minutes % (24 * 60) == 0 {
+        format!("{}d", minutes / (24 * 60))
+    } else if minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{minutes}m")
+    }
+}
+
 async fn message(ctx: &Context, command: &CommandInteraction) -> Result<()> {
     let message = command.data.resolved.messages.values().next().unwrap();
 
@@ -276,6 +415,11 @@ async fn message(ctx: &Context, command: &CommandInteraction) -> Result<()> {
                         CreateInputText::new(InputTextStyle::Paragraph, "Reason", "reason")
                             .placeholder("Short and precise reason"),
                     ),
+                    CreateActionRow::InputText(
+                        CreateInputText::new(InputTextStyle::Short, "Severity", "severity")
+                            .value(Severity::Moderate.to_string())
+                            .placeholder("minor, moderate or severe"),
+                    ),
                     CreateActionRow::InputText(
                         CreateInputText::new(InputTextStyle::Short, "User", "user")
                             .value(message.author.id.to_string()),
@@ -308,6 +452,11 @@ async fn user(ctx: &Context, command: &CommandInteraction) -> Result<()> {
                         CreateInputText::new(InputTextStyle::Paragraph, "Reason", "reason")
                             .placeholder("Short and precise reason"),
                     ),
+                    CreateActionRow::InputText(
+                        CreateInputText::new(InputTextStyle::Short, "Severity", "severity")
+                            .value(Severity::Moderate.to_string())
+                            .placeholder("minor, moderate or severe"),
+                    ),
                     CreateActionRow::InputText(
                         CreateInputText::new(InputTextStyle::Short, "User", "user")
                             .value(user.id.to_string()),
@@ -320,7 +469,7 @@ async fn user(ctx: &Context, command: &CommandInteraction) -> Result<()> {
     Ok(())
 }
 
-fn message_to_warned_message(message: &Message) -> WarnedMessage {
+pub fn message_to_warned_message(message: &Message) -> WarnedMessage {
     let mut attachments: Vec<_> = message.attachments.iter().map(|a| a.url.clone()).collect();
 
     let mut sanitized_content_parts = Vec::new();
@@ -351,7 +500,10 @@ fn message_to_warned_message(message: &Message) -> WarnedMessage {
     }
 }
 
-async fn warned_message_to_create_message(ctx: &Context, message: &WarnedMessage) -> CreateMessage {
+pub async fn warned_message_to_create_message(
+    ctx: &Context,
+    message: &WarnedMessage,
+) -> CreateMessage {
     let mut create_attachments = Vec::new();
 
     for attachment in &message.attachments {
@@ -0,0 +1,330 @@
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, CommandDataOptionValue, CommandInteraction, CommandOptionType,
+        ComponentInteraction, Mentionable, UserId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateCommand, CreateCommandOption, CreateEmbed,
+        CreateEmbedFooter, CreateInteractionResponse, CreateInteractionResponseFollowup,
+        CreateInteractionResponseMessage, CreateMessage, EditInteractionResponse,
+    },
+    client::Context,
+    model::Permissions,
+};
+use sqlx::SqlitePool;
+
+use crate::database;
+
+use super::warn::WarnedMessage;
+
+const PAGE_SIZE: usize = 5;
+
+pub fn register(name: &str, commands: &mut Vec<CreateCommand>) {
+    commands.push(
+        CreateCommand::new(name)
+            .description("Warnings")
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "user",
+                    "List the warnings of a user",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::User, "user", "User")
+                        .required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "remove",
+                    "Remove a warning",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "id", "Id").required(true),
+                ),
+            )
+            .add_option(
+                CreateCommandOption::new(
+                    CommandOptionType::SubCommand,
+                    "edit",
+                    "Edit the reason of a warning",
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::Integer, "id", "Id").required(true),
+                )
+                .add_sub_option(
+                    CreateCommandOption::new(CommandOptionType::String, "reason", "Reason")
+                        .required(true),
+                ),
+            )
+            .default_member_permissions(Permissions::MANAGE_NICKNAMES)
+            .dm_permission(false),
+    );
+}
+
+pub async fn command(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    match command.data.options[0].name.as_str() {
+        "user" => user(ctx, command, pool).await,
+        "remove" => remove(ctx, command, pool).await,
+        "edit" => edit(ctx, command, pool).await,
+        _ => Err(anyhow!("Not a subcommand")),
+    }
+}
+
+pub async fn component(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    pool: &SqlitePool,
+) -> Result<()> {
+    interaction
+        .create_response(&ctx, CreateInteractionResponse::Acknowledge)
+        .await?;
+
+    let mut parts = interaction.data.custom_id.split(':').skip(1);
+    let user = UserId::new(parts.next().ok_or_else(|| anyhow!("No user"))?.parse()?);
+    let page: usize = parts.next().ok_or_else(|| anyhow!("No page"))?.parse()?;
+
+    let (embed, components) = page_message(user, page, pool).await?;
+
+    interaction
+        .edit_response(
+            &ctx,
+            EditInteractionResponse::new()
+                .embed(embed)
+                .components(components),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn user(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let user = options[0].value.as_user_id().unwrap();
+
+    let (embed, components) = page_message(user, 0, pool).await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .embed(embed)
+                .components(components)
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn remove(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let id = options[0].value.as_i64().unwrap();
+
+    let Ok(warn) = database::get_warn_by_id(id, pool).await else {
+        return Err(anyhow!("There is no warning with id {id}"));
+    };
+
+    database::delete_warn_by_id(id, pool).await?;
+
+    ChannelId::new(1209471689264603167)
+        .send_message(
+            ctx,
+            CreateMessage::new().embed(
+                CreateEmbed::default()
+                    .color(0x00ff00)
+                    .title(format!("Removed warning {id}"))
+                    .field("User", format!("<@{}>", warn.user), true)
+                    .field("Reason", warn.reason, true)
+                    .footer(CreateEmbedFooter::new(format!(
+                        "Removed by {}",
+                        command.user.name
+                    ))),
+            ),
+        )
+        .await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!("Removed warning {id}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn edit(ctx: &Context, command: &CommandInteraction, pool: &SqlitePool) -> Result<()> {
+    command
+        .create_response(
+            &ctx,
+            CreateInteractionResponse::Defer(
+                CreateInteractionResponseMessage::new().ephemeral(true),
+            ),
+        )
+        .await?;
+
+    let CommandDataOptionValue::SubCommand(options) = &command.data.options[0].value else {
+        return Err(anyhow!("Not a subcommand"));
+    };
+
+    let id = options[0].value.as_i64().unwrap();
+    let reason = options[1].value.as_str().unwrap();
+
+    let Ok(warn) = database::get_warn_by_id(id, pool).await else {
+        return Err(anyhow!("There is no warning with id {id}"));
+    };
+
+    database::update_warn_reason_by_id(id, reason, pool).await?;
+
+    ChannelId::new(1209471689264603167)
+        .send_message(
+            ctx,
+            CreateMessage::new().embed(
+                CreateEmbed::default()
+                    .color(0xffa500)
+                    .title(format!("Edited warning {id}"))
+                    .field("User", format!("<@{}>", warn.user), true)
+                    .field("Old reason", warn.reason, true)
+                    .field("New reason", reason, true)
+                    .footer(CreateEmbedFooter::new(format!(
+                        "Edited by {}",
+                        command.user.name
+                    ))),
+            ),
+        )
+        .await?;
+
+    command
+        .create_followup(
+            &ctx,
+            CreateInteractionResponseFollowup::new()
+                .content(format!("Edited warning {id}"))
+                .ephemeral(true),
+        )
+        .await?;
+
+    Ok(())
+}
+
+async fn page_message(
+    user: UserId,
+    page: usize,
+    pool: &SqlitePool,
+) -> Result<(CreateEmbed, Vec<CreateActionRow>)> {
+    let warns = database::get_warns_by_user(user.get() as i64, pool).await?;
+
+    if warns.is_empty() {
+        return Err(anyhow!("This user has no warnings"));
+    }
+
+    let now = Utc::now().naive_utc();
+    let active = warns
+        .iter()
+        .filter(|w| w.expires.is_none_or(|e| e > now))
+        .count();
+
+    let pages = warns.len().div_ceil(PAGE_SIZE);
+    let page = page.min(pages - 1);
+
+    let fields = warns
+        .iter()
+        .skip(page * PAGE_SIZE)
+        .take(PAGE_SIZE)
+        .map(|w| {
+            let expires = match w.expires {
+                Some(e) if e <= now => "Expired".to_string(),
+                Some(e) => format!("<t:{}:R>", e.and_utc().timestamp()),
+                None => "Never".to_string(),
+            };
+
+            // Warns from before timestamps were recorded are stored with the epoch
+            let created = match w.timestamp.and_utc().timestamp() {
+                0 => "Unknown".to_string(),
+                timestamp => format!("<t:{timestamp}:f>"),
+            };
+
+            let mut value = format!(
+                "Reason: {}\nSeverity: {}\nModerator: <@{}>\nDm: {}\nCreated: {created}\nExpires: {expires}",
+                w.reason,
+                w.severity,
+                w.moderator,
+                if w.dm { "✅" } else { "❌" },
+            );
+
+            if let Some(message) = w
+                .message
+                .as_ref()
+                .and_then(|m| serde_json::from_str::<WarnedMessage>(m).ok())
+            {
+                let content = message.content.chars().take(300).collect::<String>();
+                value.push_str(&format!("\nMessage: {content}"));
+
+                for attachment in message.attachments {
+                    value.push_str(&format!("\n{attachment}"));
+                }
+            }
+
+            (
+                format!("Id {}", w.id),
+                value.chars().take(1024).collect::<String>(),
+                false,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let embed = CreateEmbed::new()
+        .color(0xff0000)
+        .title("Warnings")
+        .description(format!(
+            "{} has {} warnings ({active} active)",
+            user.mention(),
+            warns.len()
+        ))
+        .fields(fields)
+        .footer(CreateEmbedFooter::new(format!("Page {}/{pages}", page + 1)));
+
+    let name = super::ListenerName::Warns.to_string();
+
+    let components = vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{name}:{user}:{}", page.saturating_sub(1)))
+            .label("Previous")
+            .style(ButtonStyle::Secondary)
+            .disabled(page == 0),
+        CreateButton::new(format!("{name}:{user}:{}", page + 1))
+            .label("Next")
+            .style(ButtonStyle::Secondary)
+            .disabled(page + 1 >= pages),
+    ])];
+
+    Ok((embed, components))
+}
@@ -1,6 +1,7 @@
 mod database;
 mod handler;
 mod listener;
+mod settings;
 mod stardb;
 mod updater;
 
@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use anyhow::{anyhow, Result};
+use sqlx::SqlitePool;
+
+use crate::database;
+
+#[derive(Clone, Copy, strum_macros::Display, strum_macros::EnumIter, strum_macros::EnumString)]
+#[strum(serialize_all = "camelCase")]
+pub enum Setting {
+    MatchIdleWarnHours,
+    MatchIdleDisbandHours,
+    MatchCategoryName,
+    MatchCategoryLimit,
+    MatchArchiveChannel,
+    WarnMinorExpiryDays,
+    WarnModerateExpiryDays,
+    WarnSevereExpiryDays,
+    AppealChannel,
+    MessageLogChannel,
+    MessageCachePersistent,
+    MessageCacheChannelLimit,
+    MessageCacheRetentionDays,
+    BlacklistWarnThreshold,
+    BlacklistWarnDays,
+    AutomodLogChannel,
+    RaidJoinThreshold,
+    RaidAccountAgeDays,
+    RaidModeMinutes,
+    RolePersistRetentionDays,
+    WelcomeChannel,
+    WelcomeDm,
+    WelcomeMessage,
+}
+
+impl Setting {
+    pub fn default(&self) -> &'static str {
+        match self {
+            Setting::MatchIdleWarnHours => "24",
+            Setting::MatchIdleDisbandHours => "48",
+            Setting::MatchCategoryName => "💕 [matches] 💕",
+            Setting::MatchCategoryLimit => "50",
+            Setting::MatchArchiveChannel => "0",
+            Setting::WarnMinorExpiryDays => "30",
+            Setting::WarnModerateExpiryDays => "90",
+            Setting::WarnSevereExpiryDays => "0",
+            Setting::AppealChannel => "1209471689264603167",
+            Setting::MessageLogChannel => "0",
+            Setting::MessageCachePersistent => "0",
+            Setting::MessageCacheChannelLimit => "1000",
+            Setting::MessageCacheRetentionDays => "14",
+            Setting::BlacklistWarnThreshold => "0",
+            Setting::BlacklistWarnDays => "7",
+            Setting::AutomodLogChannel => "1209471689264603167",
+            Setting::RaidJoinThreshold => "10",
+            Setting::RaidAccountAgeDays => "0",
+            Setting::RaidModeMinutes => "15",
+            Setting::RolePersistRetentionDays => "180",
+            Setting::WelcomeChannel => "0",
+            Setting::WelcomeDm => "0",
+            Setting::WelcomeMessage => "Welcome to the StarDB Completionist Community, {user}!\n\nClick the \"Register\" button below and enter your UID to become a verified member and gain access to leaderboards, giveaways, and other server tools.",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Setting::MatchIdleWarnHours => "Hours of inactivity before a match partner gets warned",
+            Setting::MatchIdleDisbandHours => "Hours of inactivity before a match gets disbanded",
+            Setting::MatchCategoryName => "Name of the categories match channels are created in",
+            Setting::MatchCategoryLimit => "Maximum number of channels per match category",
+            Setting::MatchArchiveChannel => {
+                "Channel disbanded match transcripts are archived to (0 to disable)"
+            }
+            Setting::WarnMinorExpiryDays => "Days until a minor warning expires (0 for never)",
+            Setting::WarnModerateExpiryDays => {
+                "Days until a moderate warning expires (0 for never)"
+            }
+            Setting::WarnSevereExpiryDays => "Days until a severe warning expires (0 for never)",
+            Setting::AppealChannel => "Channel appeals are posted to",
+            Setting::MessageLogChannel => {
+                "Channel message edits and deletions are logged to (0 to disable)"
+            }
+            Setting::MessageCachePersistent => {
+                "Also keep cached messages in the database across restarts (1 to enable)"
+            }
+            Setting::MessageCacheChannelLimit => "Messages kept per channel in the database cache",
+            Setting::MessageCacheRetentionDays => "Days messages are kept in the database cache",
+            Setting::BlacklistWarnThreshold => {
+                "Blacklisted emoji uses within BlacklistWarnDays before a user gets warned (0 to disable)"
+            }
+            Setting::BlacklistWarnDays => "Days blacklisted emoji uses are counted for warnings",
+            Setting::AutomodLogChannel => "Channel automod actions are logged to (0 to disable)",
+            Setting::RaidJoinThreshold => {
+                "Joins per minute that start raid mode and hold new members (0 to disable)"
+            }
+            Setting::RaidAccountAgeDays => {
+                "Accounts younger than this many days are held on join (0 to disable)"
+            }
+            Setting::RaidModeMinutes => "Minutes raid mode stays active after the last trigger",
+            Setting::RolePersistRetentionDays => {
+                "Days roles of members who left are kept for restoring (0 for forever)"
+            }
+            Setting::WelcomeChannel => "Channel new members are welcomed in (0 to disable)",
+            Setting::WelcomeDm => "Whether new members get the welcome message as a dm (0 or 1)",
+            Setting::WelcomeMessage => "Welcome message, {user} is replaced with the new member",
+        }
+    }
+}
+
+pub async fn get<T: FromStr>(setting: Setting, pool: &SqlitePool) -> Result<T> {
+    let value = database::get_setting_by_key(&setting.to_string(), pool)
+        .await?
+        .map(|s| s.value)
+        .unwrap_or_else(|| setting.default().to_string());
+
+    value
+        .parse()
+        .map_err(|_| anyhow!("Invalid value {value} for setting {setting}"))
+}
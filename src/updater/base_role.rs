@@ -0,0 +1,195 @@
+use std::{collections::HashSet, sync::Arc, time::Duration};
+
+use anyhow::Result;
+use serenity::{
+    all::{Member, RoleId, UserId},
+    http::{Http, HttpError, StatusCode},
+};
+use sqlx::SqlitePool;
+use tokio::time;
+
+use crate::{database, GUILD_ID};
+
+use super::log;
+
+const NAME: &str = "base_role";
+const BASE_ROLE: u64 = 1210489410467143741;
+const MAX_TRIES: u32 = 3;
+/// Pages of 1000 members between progress logs
+const LOG_PAGES: u32 = 10;
+
+/// Gives every member the base role, continuing where the last run stopped
+pub async fn backfill(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
+    let base_role = RoleId::new(BASE_ROLE);
+    let guild_roles = GUILD_ID.roles(http).await?;
+
+    let bot = http.get_current_user().await?.id;
+    let position = GUILD_ID
+        .member(http, bot)
+        .await?
+        .roles
+        .iter()
+        .filter_map(|r| guild_roles.get(r))
+        .map(|r| r.position)
+        .max()
+        .unwrap_or_default();
+
+    let skips = database::get_backfill_skips_by_name(NAME, pool)
+        .await?
+        .into_iter()
+        .map(|s| s.user)
+        .collect::<HashSet<_>>();
+
+    let held = database::get_held_members(pool)
+        .await?
+        .into_iter()
+        .map(|m| m.user)
+        .collect::<HashSet<_>>();
+
+    let mut after = database::get_backfill_by_name(NAME, pool)
+        .await?
+        .map(|b| UserId::new(b.cursor as u64));
+
+    if let Some(after) = after {
+        log(&format!("Resuming base role backfill after {after}"), http).await;
+    }
+
+    let mut checked = 0;
+    let mut added = 0;
+    let mut skipped = 0;
+    let mut unmanageable = 0;
+    let mut left = 0;
+    let mut pages = 0;
+    let mut failures = Vec::new();
+
+    loop {
+        let members = GUILD_ID.members(http, Some(1000), after).await?;
+
+        let Some(last) = members.last() else {
+            break;
+        };
+        after = Some(last.user.id);
+
+        for member in &members {
+            checked += 1;
+
+            let user = member.user.id.get() as i64;
+
+            // Held members only get the base role once they are approved
+            if member.user.bot || member.roles.contains(&base_role) || held.contains(&user) {
+                continue;
+            }
+
+            if skips.contains(&user) {
+                skipped += 1;
+                continue;
+            }
+
+            if member
+                .roles
+                .iter()
+                .filter_map(|r| guild_roles.get(r))
+                .any(|r| r.position >= position)
+            {
+                unmanageable += 1;
+                continue;
+            }
+
+            let Err(e) = add_role(http, member, base_role).await else {
+                added += 1;
+                continue;
+            };
+
+            if GUILD_ID.member(http, member.user.id).await.is_err() {
+                left += 1;
+                continue;
+            }
+
+            let skip = database::DbBackfillSkip {
+                name: NAME.to_string(),
+                user,
+                reason: e.to_string(),
+            };
+            database::set_backfill_skip(&skip, pool).await?;
+
+            failures.push(format!("<@{user}> - {e}"));
+        }
+
+        if let Some(after) = after {
+            let backfill = database::DbBackfill {
+                name: NAME.to_string(),
+                cursor: after.get() as i64,
+            };
+            database::set_backfill(&backfill, pool).await?;
+        }
+
+        pages += 1;
+
+        if pages % LOG_PAGES == 0 {
+            log(
+                &format!("Base role backfill: checked {checked} members, added {added} roles"),
+                http,
+            )
+            .await;
+        }
+    }
+
+    // Skips only last for one full pass, so the next run retries failed members
+    database::delete_backfill_by_name(NAME, pool).await?;
+    database::delete_backfill_skips_by_name(NAME, pool).await?;
+
+    let mut summary = format!(
+        "Base role backfill done: checked {checked} members, added {added} roles, {skipped} skipped, {unmanageable} unmanageable, {left} left, {} failed",
+        failures.len()
+    );
+
+    if !failures.is_empty() {
+        summary.push_str(" <@246684413075652612>\n");
+        summary.push_str(
+            &failures
+                .iter()
+                .take(20)
+                .cloned()
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+
+        if failures.len() > 20 {
+            summary.push_str(&format!("\nAnd {} more", failures.len() - 20));
+        }
+    }
+
+    log(&summary, http).await;
+
+    Ok(())
+}
+
+/// Adds a role, retrying a few times before giving up
+pub async fn add_role(http: &Arc<Http>, member: &Member, role: RoleId) -> Result<()> {
+    let mut tries = 0;
+
+    loop {
+        match member.add_role(http, role).await {
+            Ok(()) => return Ok(()),
+            Err(e) => {
+                tries += 1;
+
+                // Missing permissions fail the same way on every try
+                if tries >= MAX_TRIES || forbidden(&e) {
+                    return Err(e.into());
+                }
+
+                time::sleep(Duration::from_secs(2u64.pow(tries))).await;
+            }
+        }
+    }
+}
+
+/// Whether discord refused the request because the bot lacks permissions for it
+pub fn forbidden(e: &serenity::Error) -> bool {
+    matches!(
+        e,
+        serenity::Error::Http(HttpError::UnsuccessfulRequest(response))
+            if response.status_code == StatusCode::FORBIDDEN
+    )
+}
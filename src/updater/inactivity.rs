@@ -0,0 +1,105 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use serenity::all::{ChannelId, CreateMessage, Http};
+use sqlx::SqlitePool;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+};
+
+pub async fn update(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
+    let warn_hours: i64 = settings::get(Setting::MatchIdleWarnHours, pool).await?;
+    let disband_hours: i64 = settings::get(Setting::MatchIdleDisbandHours, pool).await?;
+
+    for db_match in database::get_matches(pool).await? {
+        if let Err(e) = update_match(&db_match, warn_hours, disband_hours, http, pool).await {
+            super::log(
+                &format!("Error: inactivity match <#{}> {e}", db_match.channel),
+                http,
+            )
+            .await;
+        }
+    }
+
+    Ok(())
+}
+
+async fn update_match(
+    db_match: &database::DbMatch,
+    warn_hours: i64,
+    disband_hours: i64,
+    http: &Arc<Http>,
+    pool: &SqlitePool,
+) -> Result<()> {
+    let now = Utc::now().naive_utc();
+
+    let activity1 = db_match.activity1.unwrap_or(db_match.timestamp);
+    let activity2 = db_match.activity2.unwrap_or(db_match.timestamp);
+
+    let (idle, idle_since, active, active_since) = if activity1 <= activity2 {
+        (db_match.user1, activity1, db_match.user2, activity2)
+    } else {
+        (db_match.user2, activity2, db_match.user1, activity1)
+    };
+
+    if now - idle_since >= Duration::hours(disband_hours) {
+        let reason = format!("<@{idle}> was inactive for more than {disband_hours}h");
+
+        super::disband(db_match, None, Some(&reason), http, pool).await?;
+
+        let requeue = now - active_since < Duration::hours(disband_hours);
+
+        if requeue {
+            let candidate = database::DbCandidate {
+                user: active,
+                timestamp: now,
+            };
+            database::set_candidate(candidate, pool).await?;
+        }
+
+        super::log(
+            &format!(
+                "Disbanded match <#{}> of <@{}> and <@{}>. {reason}{}",
+                db_match.channel,
+                db_match.user1,
+                db_match.user2,
+                if requeue {
+                    format!(". Re-applied <@{active}>")
+                } else {
+                    String::new()
+                }
+            ),
+            http,
+        )
+        .await;
+    } else if now - idle_since >= Duration::hours(warn_hours) && db_match.warned != Some(idle) {
+        let deadline = (idle_since + Duration::hours(disband_hours))
+            .and_utc()
+            .timestamp();
+
+        ChannelId::new(db_match.channel as u64)
+            .send_message(
+                http,
+                CreateMessage::new().content(format!(
+                    "<@{idle}> you haven't been active in this match for more than {warn_hours}h. If you stay inactive, the Support Contract will end automatically <t:{deadline}:R>."
+                )),
+            )
+            .await?;
+
+        database::set_match_warned(db_match.channel, idle, pool).await?;
+
+        super::log(
+            &format!(
+                "Warned <@{idle}> for inactivity in match <#{}>",
+                db_match.channel
+            ),
+            http,
+        )
+        .await;
+    }
+
+    Ok(())
+}
@@ -1,13 +1,22 @@
-use std::{collections::HashSet, sync::Arc};
+use std::{collections::HashSet, sync::Arc, time::Duration};
 
 use anyhow::Result;
-use serenity::all::{
-    ChannelType, CreateChannel, CreateMessage, Http, PermissionOverwrite, PermissionOverwriteType,
-    Permissions, RoleId, UserId,
+use chrono::Utc;
+use serenity::{
+    all::{
+        ButtonStyle, ChannelId, ChannelType, CreateActionRow, CreateAttachment, CreateButton,
+        CreateChannel, CreateMessage, Http, PermissionOverwrite, PermissionOverwriteType,
+        Permissions, RoleId, UserId,
+    },
+    futures::StreamExt,
 };
 use sqlx::SqlitePool;
 
-use crate::database;
+use crate::{
+    database,
+    listener::ListenerName,
+    settings::{self, Setting},
+};
 
 pub async fn update(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
     let candidates = database::get_candidates(pool).await?;
@@ -25,7 +34,7 @@ pub async fn update(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
 
         let connections1 = database::get_connections_by_user(user1, pool).await?;
 
-        let user2 = {
+        let (user2, applied2, region) = {
             let mut o = None;
 
             for candidate in &candidates {
@@ -35,20 +44,24 @@ pub async fn update(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
                     continue;
                 }
 
+                if bad_partners(user1, user2, pool).await? {
+                    continue;
+                }
+
                 let connections2 = database::get_connections_by_user(user2, pool).await?;
 
-                if connections1.iter().any(|c1| {
+                if let Some(c1) = connections1.iter().find(|c1| {
                     connections2
                         .iter()
                         .any(|c2| c1.uid / 100000000 == c2.uid / 100000000)
                 }) {
-                    o = Some(user2);
+                    o = Some((user2, candidate.timestamp, c1.uid / 100000000));
                     break;
                 }
             }
 
-            if let Some(user2) = o {
-                user2
+            if let Some(o) = o {
+                o
             } else {
                 continue;
             }
@@ -77,59 +90,28 @@ pub async fn update(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
         let name1 = UserId::new(user1 as u64).to_user(http).await?.name;
         let name2 = UserId::new(user2 as u64).to_user(http).await?.name;
 
-        let channels = super::GUILD_ID.channels(http).await?;
-
-        let mut matches_categories = Vec::new();
-
-        for channel in channels.values() {
-            if channel.kind == ChannelType::Category && channel.name == "💕 [matches] 💕" {
-                matches_categories.push(channel.id.get());
-            }
-        }
-
-        let mut channel = None;
-
-        for category in matches_categories {
-            if let Ok(ch) = super::GUILD_ID
-                .create_channel(
-                    http,
-                    CreateChannel::new(format!("{name1} x {name2}"))
-                        .category(category)
-                        .permissions(permissions.clone()),
-                )
-                .await
-            {
-                channel = Some(ch);
-                break;
-            }
-        }
-
-        if channel.is_none() {
-            let category = super::GUILD_ID
-                .create_channel(
-                    http,
-                    CreateChannel::new("💕 [matches] 💕").kind(ChannelType::Category),
-                )
-                .await?;
-
-            channel = Some(
-                super::GUILD_ID
-                    .create_channel(
-                        http,
-                        CreateChannel::new(format!("{name1} x {name2}"))
-                            .category(category)
-                            .permissions(permissions.clone()),
-                    )
-                    .await?,
-            );
-        }
+        let category = match_category(http, pool).await?;
 
-        let channel = channel.unwrap();
+        let channel = super::GUILD_ID
+            .create_channel(
+                http,
+                CreateChannel::new(format!("{name1} x {name2}"))
+                    .category(category)
+                    .permissions(permissions),
+            )
+            .await?;
 
         let db_match = database::DbMatch {
             channel: channel.id.get() as i64,
             user1,
             user2,
+            region,
+            timestamp: Utc::now().naive_utc(),
+            activity1: None,
+            activity2: None,
+            warned: None,
+            applied1: Some(candidate.timestamp),
+            applied2: Some(applied2),
         };
 
         database::set_match(&db_match, pool).await?;
@@ -141,16 +123,218 @@ Amazing! Your support contractor has been found. Please use this channel to
 2. Agree on which support unit to provide to the other
 3. Ensure you know the two correct ways to give credits to each other by reading the guide https://stardb.gg/articles/how-to-get-credits-from-supports/
 4. Agree on assisting each other 10 times per day or unless otherwise agreed upon
-5. If one party is unresponsive for more than 24hrs, then ping a staff member and we can unmatch you
+5. If one party is unresponsive for more than 24hrs, you can end the contract with the button below (or /leave)
 ");
 
         channel
-            .send_message(http, CreateMessage::new().content(text))
+            .send_message(
+                http,
+                CreateMessage::new()
+                    .content(text)
+                    .components(vec![CreateActionRow::Buttons(vec![CreateButton::new(
+                        ListenerName::Leave.to_string(),
+                    )
+                    .label("Leave")
+                    .style(ButtonStyle::Danger)])]),
+            )
             .await?;
 
         database::delete_candidate_by_user(user1, pool).await?;
         database::delete_candidate_by_user(user2, pool).await?;
     }
 
+    cleanup_categories(http, pool).await?;
+
+    Ok(())
+}
+
+pub async fn disband(
+    db_match: &database::DbMatch,
+    disbander: Option<UserId>,
+    reason: Option<&str>,
+    http: &Arc<Http>,
+    pool: &SqlitePool,
+) -> Result<()> {
+    database::delete_match_by_channel(db_match.channel, pool).await?;
+
+    let history = database::DbMatchHistory {
+        channel: db_match.channel,
+        user1: db_match.user1,
+        user2: db_match.user2,
+        region: db_match.region,
+        started: db_match.timestamp,
+        ended: Utc::now().naive_utc(),
+        disbander: disbander.map(|u| u.get() as i64),
+        reason: reason.map(|r| r.to_string()),
+        rating1: None,
+        rating2: None,
+        applied1: db_match.applied1,
+        applied2: db_match.applied2,
+    };
+
+    let id = database::set_match_history(&history, pool).await?;
+
+    let text = "Your Support Contract with your partner has ended. If you would like to re-match with someone, please go back to https://discord.com/channels/1008493665116758167/1144488145228923020 and hit the Match button.";
+
+    for (user, partner) in [
+        (db_match.user1, db_match.user2),
+        (db_match.user2, db_match.user1),
+    ] {
+        if let Ok(channel) = UserId::new(user as u64).create_dm_channel(http).await {
+            let _ = channel
+                .send_message(
+                    http,
+                    CreateMessage::new()
+                        .content(format!(
+                            "{text}\n\nHow reliable was your partnership with <@{partner}>?"
+                        ))
+                        .components(rating_buttons(id)),
+                )
+                .await;
+        }
+    }
+
+    let archive: u64 = settings::get(Setting::MatchArchiveChannel, pool).await?;
+
+    {
+        let http = http.clone();
+        let channel = ChannelId::new(db_match.channel as u64);
+        let header = format!(
+            "Match {id} of <@{}> and <@{}> ({}) from <t:{}:f> to <t:{}:f>",
+            history.user1,
+            history.user2,
+            region_name(history.region),
+            history.started.and_utc().timestamp(),
+            history.ended.and_utc().timestamp(),
+        );
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+
+            if archive != 0 {
+                if let Err(e) =
+                    archive_channel(channel, ChannelId::new(archive), &header, &http).await
+                {
+                    super::log(
+                        &format!("Error: Archive match {id} {e} <@246684413075652612>"),
+                        &http,
+                    )
+                    .await;
+                }
+            }
+
+            let _ = channel.delete(&http).await;
+        });
+    }
+
     Ok(())
 }
+
+pub fn region_name(region: i64) -> &'static str {
+    match region {
+        1 | 2 | 5 => "China",
+        6 => "America",
+        7 => "Europe",
+        8 => "Asia",
+        9 => "TW, HK, MO",
+        _ => "Unknown",
+    }
+}
+
+async fn match_category(http: &Arc<Http>, pool: &SqlitePool) -> Result<ChannelId> {
+    let name: String = settings::get(Setting::MatchCategoryName, pool).await?;
+    let limit: usize = settings::get(Setting::MatchCategoryLimit, pool).await?;
+
+    let channels = super::GUILD_ID.channels(http).await?;
+
+    let mut categories = channels
+        .values()
+        .filter(|c| c.kind == ChannelType::Category && c.name == name)
+        .collect::<Vec<_>>();
+    categories.sort_by_key(|c| c.position);
+
+    for category in categories {
+        let count = channels
+            .values()
+            .filter(|c| c.parent_id == Some(category.id))
+            .count();
+
+        if count < limit {
+            return Ok(category.id);
+        }
+    }
+
+    let category = super::GUILD_ID
+        .create_channel(http, CreateChannel::new(name).kind(ChannelType::Category))
+        .await?;
+
+    Ok(category.id)
+}
+
+async fn cleanup_categories(http: &Arc<Http>, pool: &SqlitePool) -> Result<()> {
+    let name: String = settings::get(Setting::MatchCategoryName, pool).await?;
+
+    let channels = super::GUILD_ID.channels(http).await?;
+
+    let mut categories = channels
+        .values()
+        .filter(|c| c.kind == ChannelType::Category && c.name == name)
+        .collect::<Vec<_>>();
+    categories.sort_by_key(|c| c.position);
+
+    // Keep the first category around, so the next match doesn't have to create one
+    for category in categories.into_iter().skip(1) {
+        if !channels.values().any(|c| c.parent_id == Some(category.id)) {
+            category.id.delete(http).await?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn archive_channel(
+    channel: ChannelId,
+    archive: ChannelId,
+    header: &str,
+    http: &Arc<Http>,
+) -> Result<()> {
+    let mut messages = channel
+        .messages_iter(http)
+        .filter_map(|m| async move { m.ok() })
+        .collect::<Vec<_>>()
+        .await;
+    messages.reverse();
+
+    archive
+        .send_message(
+            http,
+            CreateMessage::new()
+                .content(header)
+                .add_file(CreateAttachment::bytes(
+                    super::transcript(&messages),
+                    format!("{}.txt", channel.get()),
+                )),
+        )
+        .await?;
+
+    Ok(())
+}
+
+fn rating_buttons(id: i64) -> Vec<CreateActionRow> {
+    vec![CreateActionRow::Buttons(
+        (1..=5)
+            .map(|rating| {
+                CreateButton::new(format!("{}:{id}:{rating}", ListenerName::History))
+                    .label("⭐".repeat(rating))
+                    .style(ButtonStyle::Secondary)
+            })
+            .collect(),
+    )]
+}
+
+async fn bad_partners(user1: i64, user2: i64, pool: &SqlitePool) -> Result<bool> {
+    Ok(database::get_match_histories_by_users(user1, user2, pool)
+        .await?
+        .iter()
+        .any(|h| h.rating1.is_some_and(|r| r <= 2) || h.rating2.is_some_and(|r| r <= 2)))
+}
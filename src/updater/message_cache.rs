@@ -0,0 +1,33 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+};
+
+/// Whether new messages are also written to the database, refreshed on every update
+static PERSISTENT: AtomicBool = AtomicBool::new(false);
+
+pub fn persistent() -> bool {
+    PERSISTENT.load(Ordering::Relaxed)
+}
+
+pub async fn update(pool: &SqlitePool) -> Result<()> {
+    let persistent: i64 = settings::get(Setting::MessageCachePersistent, pool).await?;
+    let limit: i64 = settings::get(Setting::MessageCacheChannelLimit, pool).await?;
+    let retention_days: i64 = settings::get(Setting::MessageCacheRetentionDays, pool).await?;
+
+    PERSISTENT.store(persistent != 0, Ordering::Relaxed);
+
+    database::delete_cached_messages_over_limit(limit, pool).await?;
+
+    database::delete_cached_messages_before(
+        (Utc::now() - Duration::days(retention_days)).naive_utc(),
+        pool,
+    )
+    .await
+}
@@ -1,8 +1,12 @@
+mod base_role;
 mod daily_reminder;
 mod gi_posts;
 mod hsr_posts;
+mod inactivity;
 mod matches;
+mod message_cache;
 mod roles;
+mod user_roles;
 mod verifications;
 mod zzz_posts;
 
@@ -14,7 +18,7 @@ use std::{
 
 use anyhow::Result;
 use serenity::{
-    all::{ChannelId, Member, RoleId},
+    all::{ChannelId, Member, Message, RoleId},
     builder::CreateMessage,
     http::Http,
 };
@@ -23,6 +27,10 @@ use tokio::time;
 
 use crate::{database, stardb, GUILD_ID};
 
+pub use base_role::{add_role, backfill as backfill_base_role, forbidden};
+pub use matches::{disband, region_name};
+pub use message_cache::persistent as message_cache_persistent;
+
 pub fn init(http: Arc<Http>, pool: SqlitePool) {
     {
         let http = http.clone();
@@ -111,6 +119,51 @@ pub fn init(http: Arc<Http>, pool: SqlitePool) {
                             &http,
                         )
                         .await;
+
+                        let now = Instant::now();
+                        if let Err(e) = inactivity::update(&http, &pool).await {
+                            log(
+                                &format!("Error: Inactivity {} <@246684413075652612>", e),
+                                &http,
+                            )
+                            .await;
+                        }
+                        log(
+                            &format!("Updated inactivity in {} seconds", now.elapsed().as_secs()),
+                            &http,
+                        )
+                        .await;
+
+                        let now = Instant::now();
+                        if let Err(e) = message_cache::update(&pool).await {
+                            log(
+                                &format!("Error: Message cache {} <@246684413075652612>", e),
+                                &http,
+                            )
+                            .await;
+                        }
+                        log(
+                            &format!(
+                                "Updated message cache in {} seconds",
+                                now.elapsed().as_secs()
+                            ),
+                            &http,
+                        )
+                        .await;
+
+                        let now = Instant::now();
+                        if let Err(e) = user_roles::update(&pool).await {
+                            log(
+                                &format!("Error: User roles {} <@246684413075652612>", e),
+                                &http,
+                            )
+                            .await;
+                        }
+                        log(
+                            &format!("Updated user roles in {} seconds", now.elapsed().as_secs()),
+                            &http,
+                        )
+                        .await;
                     }
                 });
 
@@ -297,3 +350,22 @@ pub async fn log(content: &str, http: &Arc<Http>) {
         .await
         .unwrap();
 }
+
+pub fn transcript(messages: &[Message]) -> String {
+    messages
+        .iter()
+        .map(|m| {
+            let mut line = format!(
+                "[{}] {} ({}): {}",
+                m.timestamp, m.author.name, m.author.id, m.content
+            );
+
+            for attachment in &m.attachments {
+                line.push_str(&format!("\n    {}", attachment.url));
+            }
+
+            line
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use chrono::{Duration, Utc};
+use sqlx::SqlitePool;
+
+use crate::{
+    database,
+    settings::{self, Setting},
+};
+
+pub async fn update(pool: &SqlitePool) -> Result<()> {
+    let retention_days: i64 = settings::get(Setting::RolePersistRetentionDays, pool).await?;
+
+    if retention_days <= 0 {
+        return Ok(());
+    }
+
+    database::delete_user_roles_before(
+        (Utc::now() - Duration::days(retention_days)).naive_utc(),
+        pool,
+    )
+    .await
+}